@@ -0,0 +1,176 @@
+//! Simple wall-clock benchmarks for the search functions.
+//!
+//! These use a plain `main` rather than a benchmarking framework so they build on stable without
+//! extra dependencies. Run with `cargo bench`, and compare implementations by re-running with
+//! the relevant feature enabled, e.g. `cargo bench --features recursive`.
+
+use bisection::{
+    advance_past, bisect_left, bisect_left_guarded, bisect_left_slice, bisect_left_slice_by,
+    bisect_left_uniform_aware, bisect_right, bisect_right_sorted_targets,
+    bisect_right_uniform_aware, bounds_of, count_equal, equal_range, merge_sorted, ranks_of,
+};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: usize = 1_000_000;
+
+fn bench<F>(name: &str, mut f: F)
+where
+    F: FnMut(usize) -> usize,
+{
+    let start = Instant::now();
+    let mut acc = 0;
+    for i in 0..ITERATIONS {
+        acc ^= f(i);
+    }
+    black_box(acc);
+    let elapsed = start.elapsed();
+    println!(
+        "{:<40} {:>10.2} ns/iter",
+        name,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+/// Like [`bench`], but for a function that handles all `ITERATIONS` inputs in one call.
+fn bench_batch<F>(name: &str, f: F)
+where
+    F: FnOnce() -> usize,
+{
+    let start = Instant::now();
+    black_box(f());
+    let elapsed = start.elapsed();
+    println!(
+        "{:<40} {:>10.2} ns/iter",
+        name,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+fn main() {
+    let large: Vec<u64> = (0..10_000_000).map(|i| i * 2).collect();
+    let targets: Vec<u64> = (0..ITERATIONS as u64)
+        .map(|i| i.wrapping_mul(2_654_435_761) % 20_000_000)
+        .collect();
+
+    bench("bisect_left (10M elements)", |i| {
+        bisect_left(black_box(&large), &targets[i])
+    });
+    bench("bisect_right (10M elements)", |i| {
+        bisect_right(black_box(&large), &targets[i])
+    });
+
+    // A single repeated value, answered without a descent
+    let constant = vec![10_000_000_u64; 10_000_000];
+    bench("bisect_left_uniform_aware (10M equal elements)", |i| {
+        bisect_left_uniform_aware(black_box(&constant), &targets[i])
+    });
+    bench("bisect_right_uniform_aware (10M equal elements)", |i| {
+        bisect_right_uniform_aware(black_box(&constant), &targets[i])
+    });
+
+    // Runs of 16 equal elements, so every search forks after reaching one
+    let runs: Vec<u64> = (0..10_000_000).map(|i| i / 16).collect();
+    bench("equal_range (10M elements)", |i| {
+        equal_range(black_box(&runs), &(targets[i] / 32)).len()
+    });
+    bench("count_equal (10M elements)", |i| {
+        count_equal(black_box(&runs), &(targets[i] / 32))
+    });
+    bench("bounds_of (10M elements)", |i| {
+        bounds_of(black_box(&runs), &(targets[i] / 32)).1
+    });
+
+    // With many sorted targets against a short array, the linear walk beats a bisection per
+    // target. Both are reported per target.
+    let mut sorted_targets = targets.clone();
+    sorted_targets.sort_unstable();
+    let short = &large[..ITERATIONS];
+    bench("bisect_right per target (1M elements)", |i| {
+        bisect_right(black_box(short), &sorted_targets[i])
+    });
+    bench_batch("bisect_right_sorted_targets (1M elements)", || {
+        bisect_right_sorted_targets(black_box(short), &sorted_targets)
+            .into_iter()
+            .fold(0, |acc, i| acc ^ i)
+    });
+
+    // Sparse data spread over many buckets, most of which are empty
+    let buckets: Vec<Vec<u64>> = (0..1024_u64)
+        .map(|b| {
+            if b % 64 == 0 {
+                (0..16).collect()
+            } else {
+                Vec::new()
+            }
+        })
+        .collect();
+    bench("bisect_left (mostly empty buckets)", |i| {
+        bisect_left(black_box(&buckets[i % buckets.len()]), &targets[i])
+    });
+
+    // Seven in eight targets are past the end of the array, as when filtering
+    let outside: Vec<u64> = (0..ITERATIONS)
+        .map(|i| targets[i] + if i % 8 == 0 { 0 } else { 20_000_000 })
+        .collect();
+    bench("bisect_left (mostly out of range)", |i| {
+        bisect_left(black_box(&large), &outside[i])
+    });
+    bench("bisect_left_guarded (mostly out of range)", |i| {
+        bisect_left_guarded(black_box(&large), &outside[i])
+    });
+
+    // Many searches in one tight loop, where the call overhead of a search that isn't inlined
+    // would be a large share of each one. Compare against a build with the `#[inline]`
+    // attributes removed to see their effect.
+    let small = &large[..1024];
+    bench_batch("bisect_left loop (1K elements)", || {
+        targets.iter().fold(0, |acc, t| {
+            acc ^ bisect_left(black_box(small), &(t / 16_384))
+        })
+    });
+    bench_batch("bisect_left_slice_by loop (1K elements)", || {
+        targets.iter().fold(0, |acc, t| {
+            acc ^ bisect_left_slice_by(black_box(small), 16.., |e| e.cmp(&(t / 16_384)))
+        })
+    });
+
+    // A large unsorted batch, answered in input order. Both are reported per query.
+    bench("bisect_left per query (10M elements)", |i| {
+        bisect_left(black_box(&large), &targets[i])
+    });
+    bench_batch("ranks_of (10M elements)", || {
+        ranks_of(black_box(&large), &targets)
+            .into_iter()
+            .fold(0, |acc, i| acc ^ i)
+    });
+
+    // Degenerate buckets of one element each, answered with a single comparison
+    let singletons: Vec<[u64; 1]> = (0..1024_u64).map(|b| [b * 20_000]).collect();
+    bench("bisect_left_slice (singleton slices)", |i| {
+        bisect_left_slice(
+            black_box(&singletons[i % singletons.len()]),
+            &targets[i],
+            ..,
+        )
+    });
+
+    // A sparse short run merged into a dense long one, element by element or by jumping over
+    // whole blocks of the long run. Both are reported per element of the long run.
+    let sparse: Vec<u64> = (0..64).map(|i| i * 31_250).collect();
+    bench_batch("merge_sorted (1M + 64 elements)", || {
+        merge_sorted(black_box(short), black_box(&sparse)).len()
+    });
+    bench_batch("advance_past merge (1M + 64 elements)", || {
+        let mut merged = Vec::with_capacity(short.len() + sparse.len());
+        let mut rest = short;
+        for pivot in black_box(&sparse) {
+            let k = advance_past(rest, pivot);
+            merged.extend_from_slice(&rest[..k]);
+            merged.push(*pivot);
+            rest = &rest[k..];
+        }
+        merged.extend_from_slice(rest);
+        merged.len()
+    });
+}
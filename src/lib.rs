@@ -1,8 +1,14 @@
+//! Bisection algorithms for sorted slices, modelled on Python's `bisect` module.
+
 pub use crate::bisect_right as bisect;
 pub use crate::insort_right as insort;
 
 use std::cmp::Ordering;
-use std::ops::{Bound::*, RangeBounds};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::{Add, Bound::*, Div, Range, RangeBounds, Rem, Sub};
+use std::sync::{Arc, Weak};
 
 // TODO: Doctest examples
 
@@ -40,7 +46,6 @@ where
 /// If `a` contains `x`, insert it just *after* the *rightmost* occurence of `x`.
 pub fn insort_right_by<T, F>(a: &mut Vec<T>, x: T, f: F)
 where
-    T: Ord,
     F: FnMut(&T, &T) -> Ordering,
 {
     insort_right_slice_by(a, x, .., f);
@@ -74,24 +79,29 @@ where
 /// - If `a` contains `x`, `a.insert(i, x)` will insert just *after* the
 ///   *rightmost* `x`.
 ///
+/// An empty `within` returns its start straight away, without comparing anything.
+///
 /// # Panics
 ///
 /// Panics if `within` is out of bounds of `a`.
+#[inline]
 pub fn bisect_right_slice<T, I>(a: &[T], x: &T, within: I) -> usize
 where
     I: RangeBounds<usize>,
     T: PartialOrd,
 {
-    let (mut lo, mut hi) = bounds_to_indices(a, within);
-    while lo < hi {
-        let mid = (lo + hi) / 2;
-        if *x < a[mid] {
-            hi = mid;
+    let (lo, hi) = bounds_to_indices(a, within);
+    if lo >= hi {
+        return lo;
+    }
+    if hi - lo == 1 {
+        return if x.partial_cmp(&a[lo]) != Some(Ordering::Less) {
+            hi
         } else {
-            lo = mid + 1;
-        }
+            lo
+        };
     }
-    lo
+    search(lo, hi, |mid| x.partial_cmp(&a[mid]) != Some(Ordering::Less))
 }
 
 /// Return the index where `x` should be inserted in `a`, assuming `a` is sorted.
@@ -100,6 +110,7 @@ where
 /// all `e` in `a[i..]` have `e > x`.
 /// - If `a` contains `x`, `a.insert(i, x)` will insert just *after* the
 ///   *rightmost* occurence of `x`.
+#[inline]
 pub fn bisect_right<T>(a: &[T], x: &T) -> usize
 where
     T: PartialOrd,
@@ -107,6 +118,31 @@ where
     bisect_right_slice(a, x, ..)
 }
 
+/// Return the index where `x` should be inserted in `a`, as [`bisect_right`], answering a
+/// constant `a` with two comparisons.
+///
+/// The ends of `a` are compared with each other first, which costs one extra comparison on
+/// every other slice. Use this when `a` is often a single repeated value.
+#[inline]
+pub fn bisect_right_uniform_aware<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    if a.is_empty() {
+        return 0;
+    }
+    // A slice whose ends are equal is a single repeated value, so one more comparison decides
+    // the answer without a descent
+    if a[0] == a[a.len() - 1] {
+        return if x.partial_cmp(&a[0]) != Some(Ordering::Less) {
+            a.len()
+        } else {
+            0
+        };
+    }
+    bisect_right_slice(a, x, ..)
+}
+
 /// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, according to
 /// a comparator function.
 ///
@@ -118,6 +154,7 @@ where
 /// all `e` in `a[i..]` have `f(e) == Greater`.
 /// - If `a` contains `x`, `a.insert(i, x)` will insert just *after* the
 ///   *rightmost* occurence of `x`.
+#[inline]
 pub fn bisect_right_by<T, F>(a: &[T], f: F) -> usize
 where
     F: FnMut(&T) -> Ordering,
@@ -137,24 +174,25 @@ where
 /// - If `a` contains `x`, `a.insert(i, x)` will insert just *after* the
 ///   *rightmost* occurence of `x`.
 ///
+/// An empty `within` returns its start without calling `f`.
+///
 /// # Panics
 ///
 /// Panics if `within` is out of bounds of `a`.
+#[inline]
 pub fn bisect_right_slice_by<T, I, F>(a: &[T], within: I, mut f: F) -> usize
 where
     I: RangeBounds<usize>,
     F: FnMut(&T) -> Ordering,
 {
-    let (mut lo, mut hi) = bounds_to_indices(a, within);
-    while lo < hi {
-        let mid = (lo + hi) / 2;
-        if f(&a[mid]) == Ordering::Less {
-            hi = mid;
-        } else {
-            lo = mid + 1;
-        }
+    let (lo, hi) = bounds_to_indices(a, within);
+    if lo >= hi {
+        return lo;
     }
-    lo
+    if hi - lo == 1 {
+        return if f(&a[lo]) != Ordering::Less { hi } else { lo };
+    }
+    search(lo, hi, |mid| f(&a[mid]) != Ordering::Less)
 }
 
 /// Insert `x` in `a[within]`, keeping it sorted assuming `a` is sorted.
@@ -192,7 +230,6 @@ where
 /// If `a` contains `x`, insert it just *before* the *leftmost* occurence of `x`.
 pub fn insort_left_by<T, F>(a: &mut Vec<T>, x: T, f: F)
 where
-    T: Ord,
     F: FnMut(&T, &T) -> Ordering,
 {
     insort_left_slice_by(a, x, .., f);
@@ -214,7 +251,7 @@ where
     I: RangeBounds<usize>,
     F: FnMut(&T, &T) -> Ordering,
 {
-    let lo = bisect_right_slice_by(a, within, |p| f(&x, p));
+    let lo = bisect_left_slice_by(a, within, |p| f(p, &x));
     a.insert(lo, x);
 }
 
@@ -226,24 +263,25 @@ where
 /// - If `a` contains `x`, `a.insert(i, x)` will insert just *before* the
 ///   *leftmost* `x`.
 ///
+/// An empty `within` returns its start straight away, without comparing anything.
+///
 /// # Panics
 ///
 /// Panics if `within` is out of bounds of `a`.
+#[inline]
 pub fn bisect_left_slice<T, I>(a: &[T], x: &T, within: I) -> usize
 where
     I: RangeBounds<usize>,
     T: PartialOrd,
 {
-    let (mut lo, mut hi) = bounds_to_indices(a, within);
-    while lo < hi {
-        let mid = (lo + hi) / 2;
-        if a[mid] < *x {
-            lo = mid + 1;
-        } else {
-            hi = mid;
-        }
+    let (lo, hi) = bounds_to_indices(a, within);
+    if lo >= hi {
+        return lo;
     }
-    lo
+    if hi - lo == 1 {
+        return if a[lo] < *x { hi } else { lo };
+    }
+    search(lo, hi, |mid| a[mid] < *x)
 }
 
 /// Return the index where `x` should be inserted in `a`, assuming `a` is sorted.
@@ -252,6 +290,7 @@ where
 /// all `e` in `a[i..]` have `e >= x`.
 /// - If `a` contains `x`, `a.insert(i, x)` will insert just *before* the
 ///   *leftmost* `x`.
+#[inline]
 pub fn bisect_left<T>(a: &[T], x: &T) -> usize
 where
     T: PartialOrd,
@@ -259,6 +298,27 @@ where
     bisect_left_slice(a, x, ..)
 }
 
+/// Return the index where `x` should be inserted in `a`, as [`bisect_left`], answering a
+/// constant `a` with two comparisons.
+///
+/// The ends of `a` are compared with each other first, which costs one extra comparison on
+/// every other slice. Use this when `a` is often a single repeated value.
+#[inline]
+pub fn bisect_left_uniform_aware<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    if a.is_empty() {
+        return 0;
+    }
+    // A slice whose ends are equal is a single repeated value, so one more comparison decides
+    // the answer without a descent
+    if a[0] == a[a.len() - 1] {
+        return if a[0] < *x { a.len() } else { 0 };
+    }
+    bisect_left_slice(a, x, ..)
+}
+
 /// Return the index where a value should be inserted in `a`, assuming `a` is sorted, according to
 /// a comparator function.
 ///
@@ -270,6 +330,23 @@ where
 /// all `e` in `a[i..]` have `f(e) == Greater | f(e) == Equal`
 /// - If `a` contains `x`, `a.insert(i, x)` will insert just *before* the
 ///   *leftmost* `x`.
+///
+/// # Examples
+///
+/// Searching a slice sorted by two chained keys, either by both keys or by a prefix of them:
+///
+/// ```
+/// use bisection::bisect_left_by;
+///
+/// let mut a = vec![(2, 'b'), (1, 'z'), (2, 'a'), (1, 'a'), (3, 'c')];
+/// a.sort_by(|x, y| x.0.cmp(&y.0).then(x.1.cmp(&y.1)));
+///
+/// // Both keys
+/// assert_eq!(bisect_left_by(&a, |e| e.0.cmp(&2).then(e.1.cmp(&'b'))), 3);
+/// // First key only: the start of the `2` group
+/// assert_eq!(bisect_left_by(&a, |e| e.0.cmp(&2)), 2);
+/// ```
+#[inline]
 pub fn bisect_left_by<T, F>(a: &[T], f: F) -> usize
 where
     F: FnMut(&T) -> Ordering,
@@ -288,112 +365,4486 @@ where
 /// all `e` in `a[i..]` have `f(e) == Greater | f(e) == Equal`
 /// - If `a` contains `x`, `a.insert(i, x)` will insert just *before* the
 ///   *leftmost* `x`.
+///
+/// An empty `within` returns its start without calling `f`.
+///
 /// # Panics
 ///
 /// Panics if `within` is out of bounds of `a`.
+#[inline]
 pub fn bisect_left_slice_by<T, I, F>(a: &[T], within: I, mut f: F) -> usize
 where
     I: RangeBounds<usize>,
     F: FnMut(&T) -> Ordering,
 {
-    let (mut lo, mut hi) = bounds_to_indices(a, within);
+    let (lo, hi) = bounds_to_indices(a, within);
+    if lo >= hi {
+        return lo;
+    }
+    if hi - lo == 1 {
+        return if f(&a[lo]) == Ordering::Less { hi } else { lo };
+    }
+    search(lo, hi, |mid| f(&a[mid]) == Ordering::Less)
+}
+
+/// Return the `(lt_count, le_count)` pair for `pivot` in `a`, assuming `a` is sorted.
+///
+/// `lt_count` is the number of elements `e` with `e < pivot` and `le_count` the number with
+/// `e <= pivot`, so `a[lt_count..le_count]` is the run of elements equal to `pivot`. A
+/// quickselect-style caller looking for the `k`-th smallest element can route on the result
+/// directly:
+/// - `k < lt_count`: the element is below the pivot.
+/// - `lt_count <= k < le_count`: the element is equal to the pivot.
+/// - `k >= le_count`: the element is above the pivot.
+pub fn partition_around<T>(a: &[T], pivot: &T) -> (usize, usize)
+where
+    T: PartialOrd,
+{
+    (bisect_left(a, pivot), bisect_right(a, pivot))
+}
+
+/// Return the index where a value should be inserted in `a`, assuming `a` is sorted, according to
+/// a comparator function, along with whether an element comparing `Equal` was found.
+///
+/// `f` follows the same convention as [`bisect_left_by`], and the returned index is the same.
+/// The flag is `true` exactly when `a` contains an element for which `f` returns `Equal`, in which
+/// case that element is at the returned index. It is computed during the search, so no extra
+/// comparison is needed afterwards.
+pub fn probe_left<T, F>(a: &[T], mut f: F) -> (usize, bool)
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let mut found = false;
+    let i = search(0, a.len(), |mid| match f(&a[mid]) {
+        Ordering::Less => true,
+        Ordering::Equal => {
+            found = true;
+            false
+        }
+        Ordering::Greater => false,
+    });
+    (i, found)
+}
+
+/// Return the index where a value should be inserted in `a`, assuming `a` is sorted, according to
+/// a comparator function, along with whether an element comparing `Equal` was found.
+///
+/// `f` follows the same convention as [`bisect_right_by`], and the returned index is the same.
+/// The flag is `true` exactly when `a` contains an element for which `f` returns `Equal`, in which
+/// case that element is just *before* the returned index. It is computed during the search, so no
+/// extra comparison is needed afterwards.
+pub fn probe_right<T, F>(a: &[T], mut f: F) -> (usize, bool)
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let mut found = false;
+    let i = search(0, a.len(), |mid| match f(&a[mid]) {
+        Ordering::Less => false,
+        Ordering::Equal => {
+            found = true;
+            true
+        }
+        Ordering::Greater => true,
+    });
+    (i, found)
+}
+
+/// Return the index where `x` should be inserted in the merged sequence of `runs`, assuming each
+/// run is sorted.
+///
+/// This is the total number of elements `e` with `e < x` across all runs, i.e. the result of
+/// [`bisect_left`] on the sorted concatenation of `runs`, without materialising it.
+pub fn bisect_left_runs<T>(runs: &[&[T]], x: &T) -> usize
+where
+    T: Ord,
+{
+    runs.iter().map(|run| bisect_left(run, x)).sum()
+}
+
+/// Return the number of elements equal to `x` across all of `runs`, assuming each run is sorted.
+pub fn count_equal_runs<T>(runs: &[&[T]], x: &T) -> usize
+where
+    T: Ord,
+{
+    runs.iter()
+        .map(|run| bisect_right(run, x) - bisect_left(run, x))
+        .sum()
+}
+
+/// Return the index where a value should be inserted in `a`, assuming `a` is sorted by a chain of
+/// comparators.
+///
+/// Each comparator follows the same convention as [`bisect_left_by`]. They are applied in order,
+/// stopping at the first that doesn't return `Equal`, mirroring a sort by
+/// `cmp1.then_with(cmp2)...`. Passing only a prefix of the chain searches by those keys alone.
+pub fn bisect_left_chained<T>(a: &[T], cmps: &[&dyn Fn(&T) -> Ordering]) -> usize {
+    bisect_left_by(a, |e| {
+        cmps.iter()
+            .map(|cmp| cmp(e))
+            .find(|ord| *ord != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    })
+}
+
+/// Copy `src` into `dst` with `x` inserted, keeping it sorted assuming `src` is sorted, and return
+/// the index `x` was written to.
+///
+/// If `src` contains `x`, insert it just *after* the *rightmost* occurence of `x`. Nothing is
+/// allocated, so `dst` can be reused across many inserts.
+///
+/// # Panics
+///
+/// Panics if `dst.len() != src.len() + 1`.
+pub fn insort_into_right<T>(src: &[T], x: T, dst: &mut [T]) -> usize
+where
+    T: Ord + Copy,
+{
+    assert_eq!(
+        dst.len(),
+        src.len() + 1,
+        "destination length must be one more than the source length"
+    );
+    let i = bisect_right(src, &x);
+    dst[..i].copy_from_slice(&src[..i]);
+    dst[i] = x;
+    dst[i + 1..].copy_from_slice(&src[i..]);
+    i
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, making at most
+/// `max_probes` comparisons.
+///
+/// Returns `Ok(i)` with the same index as [`bisect_left`] if the search converged within the
+/// budget. Otherwise returns `Err(lo)`, the lower bound of the narrowest bracket `lo..=hi` known
+/// to contain the insertion point when the budget ran out.
+pub fn bisect_left_budgeted<T>(a: &[T], x: &T, max_probes: usize) -> Result<usize, usize>
+where
+    T: PartialOrd,
+{
+    let (mut lo, mut hi) = (0, a.len());
+    let mut probes = 0;
     while lo < hi {
-        let mid = (lo + hi) / 2;
-        let cmp = f(&a[mid]);
-        if cmp == Ordering::Less {
+        if probes == max_probes {
+            return Err(lo);
+        }
+        probes += 1;
+        let mid = lo + (hi - lo) / 2;
+        if a[mid] < *x {
             lo = mid + 1;
         } else {
             hi = mid;
         }
     }
-    lo
+    Ok(lo)
 }
 
-/// Convert bounds to a `(lo, hi)`  pair for indexing into a slice of `a`.
+/// Return the index and a reference to the *leftmost* element equal to `x` in `a`, assuming `a`
+/// is sorted, or `None` if `a` doesn't contain `x`.
+pub fn bisect_left_entry<'a, T>(a: &'a [T], x: &T) -> Option<(usize, &'a T)>
+where
+    T: PartialOrd,
+{
+    let i = bisect_left(a, x);
+    a.get(i).filter(|e| *e == x).map(|e| (i, e))
+}
+
+/// Return the index and a reference to the *leftmost* element of `a` for which `f` returns
+/// `Equal`, assuming `a` is sorted, or `None` if there is no such element.
 ///
-/// # Panics
+/// `f` follows the same convention as [`bisect_left_by`].
+pub fn bisect_left_entry_by<T, F>(a: &[T], mut f: F) -> Option<(usize, &T)>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let i = bisect_left_by(a, &mut f);
+    a.get(i).filter(|e| f(e) == Ordering::Equal).map(|e| (i, e))
+}
+
+/// Return a reference to the value of the entry with key `key` in `entries`, assuming `entries`
+/// is sorted by key, or `None` if there is no such entry.
 ///
-/// Panics if `within` is out of bounds of `a`.
-fn bounds_to_indices<T, I>(a: &[T], within: I) -> (usize, usize)
+/// If several entries share `key`, the *leftmost* is returned.
+pub fn bisect_get<'a, K, V>(entries: &'a [(K, V)], key: &K) -> Option<&'a V>
 where
-    I: RangeBounds<usize>,
+    K: Ord,
 {
-    // TODO: Panics
-    let lo = match within.start_bound() {
-        Unbounded => 0,
-        Included(i) => *i,
-        Excluded(i) => i + 1,
-    };
+    let i = bisect_left_by(entries, |e| e.0.cmp(key));
+    entries.get(i).filter(|e| e.0 == *key).map(|e| &e.1)
+}
 
-    let hi = match within.end_bound() {
-        Unbounded => a.len(),
-        Included(i) => i + 1,
-        Excluded(i) => *i,
+/// Return a mutable reference to the value of the entry with key `key` in `entries`, assuming
+/// `entries` is sorted by key, or `None` if there is no such entry.
+///
+/// If several entries share `key`, the *leftmost* is returned.
+pub fn bisect_get_mut<'a, K, V>(entries: &'a mut [(K, V)], key: &K) -> Option<&'a mut V>
+where
+    K: Ord,
+{
+    let i = bisect_left_by(entries, |e| e.0.cmp(key));
+    entries.get_mut(i).filter(|e| e.0 == *key).map(|e| &mut e.1)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted cyclically with
+/// the given `period`.
+///
+/// `a` is expected to hold values in `0..period` arranged as a sorted sequence rotated around a
+/// single wrap point: ascending from `a[0]` up to some `a[k - 1]`, then continuing from the
+/// smallest values again, ascending up to `a[len - 1] <= a[0]`. For example angles
+/// `[300.0, 350.0, 10.0, 40.0]`. An unrotated sorted slice is also valid. If `a[len - 1]` equals
+/// `a[0]` and `a` isn't constant, the trailing run of elements equal to `a[0]` is taken to be
+/// after the wrap point, which costs a scan of that run.
+///
+/// `x` is first reduced into `0..period`, taking `T::default()` as zero, without ever
+/// exceeding `period`, so it can't overflow unsigned types. The insertion point follows the
+/// cyclic order starting at `a[0]`. If `a` contains `x`, insertion is just *after* the
+/// *rightmost* occurence of `x`.
+pub fn bisect_cyclic<T>(a: &[T], x: &T, period: T) -> usize
+where
+    T: PartialOrd + Copy + Default + Add<Output = T> + Rem<Output = T>,
+{
+    let first = match a.first() {
+        Some(first) => first,
+        None => return 0,
     };
+    let r = *x % period;
+    let x = if r < T::default() { r + period } else { r };
+    // Everything before the wrap point is `>= a[0]`, everything after is `<= a[0]`, and only
+    // equal to it in a trailing run
+    let trailing = a.iter().rev().take_while(|e| *e == first).count();
+    let end = if trailing == a.len() {
+        a.len()
+    } else {
+        a.len() - trailing
+    };
+    let wrap = a[..end].partition_point(|e| e >= first);
+    if x >= *first {
+        bisect_right_slice(a, &x, ..wrap)
+    } else {
+        bisect_right_slice(a, &x, wrap..)
+    }
+}
 
-    if hi > a.len() {
-        panic!("index out of bounds")
+/// A token recording an insertion made by [`insort_right_undoable`], which can be used to undo
+/// it.
+///
+/// The token is only valid while the vector is unchanged since the insertion; undoing after
+/// other modifications will remove the wrong element.
+#[derive(Debug, PartialEq, Eq)]
+#[must_use]
+pub struct Undo {
+    index: usize,
+}
+
+impl Undo {
+    /// Return the index the element was inserted at.
+    pub fn index(&self) -> usize {
+        self.index
     }
 
-    (lo, hi)
+    /// Remove the inserted element from `a` and return it, restoring `a` to its prior state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the insertion index is out of bounds of `a`.
+    pub fn undo<T>(self, a: &mut Vec<T>) -> T {
+        a.remove(self.index)
+    }
 }
 
-#[cfg(test)]
-mod tests {
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, and return an [`Undo`] token
+/// which can remove it again.
+///
+/// If `a` contains `x`, insert it just *after* the *rightmost* occurence of `x`.
+pub fn insort_right_undoable<T>(a: &mut Vec<T>, x: T) -> Undo
+where
+    T: Ord,
+{
+    let index = bisect_right(a, &x);
+    a.insert(index, x);
+    Undo { index }
+}
 
-    use super::*;
-    use proptest::prelude::*;
-    use std::collections::HashSet;
-    use std::iter::FromIterator;
+/// Return the index where a value should be inserted in `a`, assuming `a` is sorted, according to
+/// a dynamically dispatched comparator function.
+///
+/// This behaves like [`bisect_left_by`], but is only instantiated once per `T` however many
+/// comparators are used, so the comparator can be chosen at runtime without growing the binary.
+pub fn bisect_left_dyn<T>(a: &[T], cmp: &dyn Fn(&T) -> Ordering) -> usize {
+    bisect_left_by(a, cmp)
+}
 
-    #[derive(Clone, Debug)]
-    struct BisectTest<T: 'static>
-    where
-        T: PartialOrd,
-    {
-        name: &'static str,
-        a: &'static [T],
-        x: T,
-        expected_index: usize,
+/// Return the index where a value should be inserted in `a`, assuming `a` is sorted, according to
+/// a dynamically dispatched comparator function.
+///
+/// This behaves like [`bisect_right_by`], but is only instantiated once per `T` however many
+/// comparators are used, so the comparator can be chosen at runtime without growing the binary.
+pub fn bisect_right_dyn<T>(a: &[T], cmp: &dyn Fn(&T) -> Ordering) -> usize {
+    bisect_right_by(a, cmp)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted by the values
+/// its references point to.
+///
+/// This is [`bisect_left`] comparing the referents directly, so `x` can be a plain `&T`.
+pub fn bisect_left_deref<T>(a: &[&T], x: &T) -> usize
+where
+    T: PartialOrd + ?Sized,
+{
+    search(0, a.len(), |mid| a[mid] < x)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted by the values
+/// its references point to.
+///
+/// This is [`bisect_right`] comparing the referents directly, so `x` can be a plain `&T`.
+pub fn bisect_right_deref<T>(a: &[&T], x: &T) -> usize
+where
+    T: PartialOrd + ?Sized,
+{
+    search(0, a.len(), |mid| {
+        x.partial_cmp(a[mid]) != Some(Ordering::Less)
+    })
+}
+
+/// Insert `x` in `a`, keeping it sorted by the values its references point to, assuming `a` is
+/// sorted.
+///
+/// If `a` contains `x`, insert it just *before* the *leftmost* occurence of `x`.
+pub fn insort_left_deref<'a, T>(a: &mut Vec<&'a T>, x: &'a T)
+where
+    T: PartialOrd + ?Sized,
+{
+    let lo = bisect_left_deref(a, x);
+    a.insert(lo, x);
+}
+
+/// Insert `x` in `a`, keeping it sorted by the values its references point to, assuming `a` is
+/// sorted.
+///
+/// If `a` contains `x`, insert it just *after* the *rightmost* occurence of `x`.
+pub fn insort_right_deref<'a, T>(a: &mut Vec<&'a T>, x: &'a T)
+where
+    T: PartialOrd + ?Sized,
+{
+    let lo = bisect_right_deref(a, x);
+    a.insert(lo, x);
+}
+
+/// A streaming median built on [`insort`].
+///
+/// Values are kept in a single sorted `Vec`, so [`push`](RunningMedian::push) costs `O(log n)`
+/// comparisons but `O(n)` element moves, while reading the median is `O(1)`. For large streams, a
+/// pair of heaps (a max-heap of the lower half and a min-heap of the upper half) gives `O(log n)`
+/// inserts instead.
+#[derive(Debug, Clone, Default)]
+pub struct RunningMedian<T> {
+    values: Vec<T>,
+}
+
+impl<T> RunningMedian<T>
+where
+    T: Ord + Clone,
+{
+    /// Create an empty `RunningMedian`.
+    pub fn new() -> Self {
+        RunningMedian { values: Vec::new() }
     }
 
-    #[derive(Debug, Clone)]
-    enum TestDirection {
-        Left,
-        Right,
+    /// Add `x` to the stream.
+    pub fn push(&mut self, x: T) {
+        insort(&mut self.values, x);
     }
 
-    type TestCollection<T> = &'static [BisectTest<T>];
+    /// Return the median of the values seen so far, or `None` if there are none.
+    ///
+    /// For an even number of values this is the lower of the two middle values; see
+    /// [`median_averaged`](RunningMedian::median_averaged) for the mean of both.
+    pub fn median(&self) -> Option<T> {
+        if self.values.is_empty() {
+            return None;
+        }
+        Some(self.values[(self.values.len() - 1) / 2].clone())
+    }
 
-    macro_rules! t {
-        ($name:ident, $a:expr, $x:expr, $expected_index:expr) => {
-            BisectTest {
-                name: stringify!($name),
-                a: $a,
-                x: $x,
-                expected_index: $expected_index,
-            }
+    /// Return the number of values seen so far.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Return `true` if no values have been seen.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Return the values seen so far, in sorted order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+}
+
+impl<T> RunningMedian<T>
+where
+    T: Ord
+        + Clone
+        + Add<Output = T>
+        + Sub<Output = T>
+        + Div<Output = T>
+        + Rem<Output = T>
+        + From<u8>,
+{
+    /// Return the median of the values seen so far, or `None` if there are none.
+    ///
+    /// For an even number of values this is the mean of the two middle values. Each is halved
+    /// before they're added, so it can't overflow even at the ends of `T`'s range. For integers
+    /// it rounds down, so a mean of `-3.5` gives `-4`.
+    pub fn median_averaged(&self) -> Option<T> {
+        let n = self.values.len();
+        if n % 2 == 1 {
+            return self.median();
+        }
+        let lower = self.values.get((n / 2).checked_sub(1)?)?.clone();
+        let upper = self.values[n / 2].clone();
+        let two = T::from(2);
+        let rem = lower.clone() % two.clone() + upper.clone() % two.clone();
+        let half = lower / two.clone() + upper / two.clone();
+        // Both halves truncate towards zero, so fold the remainders back in rounding down
+        let carry = if rem < T::from(0) {
+            (rem - T::from(1)) / two
+        } else {
+            rem / two
         };
+        Some(half + carry)
     }
+}
 
-    const RIGHT_INT_CASES: TestCollection<i32> = &[
-        t!(ints_right_0, &[], 1, 0),
-        t!(ints_right_1, &[1], 0, 0),
-        t!(ints_right_2, &[1], 1, 1),
-        t!(ints_right_3, &[1], 2, 1),
-        t!(ints_right_4, &[1, 1], 0, 0),
-        t!(ints_right_5, &[1, 1], 1, 2),
-        t!(ints_right_6, &[1, 1], 2, 2),
-        t!(ints_right_7, &[1, 1, 1], 0, 0),
-        t!(ints_right_8, &[1, 1, 1], 1, 3),
-        t!(ints_right_9, &[1, 1, 1], 2, 3),
-        t!(ints_right_10, &[1, 1, 1, 1], 0, 0),
-        t!(ints_right_11, &[1, 1, 1, 1], 1, 4),
-        t!(ints_right_12, &[1, 1, 1, 1], 2, 4),
-        t!(ints_right_13, &[1, 2], 0, 0),
-        t!(ints_right_14, &[1, 2], 1, 1),
-        t!(ints_right_15, &[1, 2], 2, 2),
-        t!(ints_right_16, &[1, 2], 3, 2),
+/// Return the indices of `x` on the descending and ascending arms of `a`, assuming `a` is
+/// valley-shaped: strictly decreasing down to a single minimum, then strictly increasing.
+///
+/// The minimum is located by binary search on the direction of each adjacent pair, then each arm
+/// is bisected separately. The first element of the result is the index of `x` on the descending
+/// arm `a[..=bottom]` and the second on the ascending arm `a[bottom..]`, with `None` where `x`
+/// isn't on that arm. If `x` is the minimum itself, both are `Some(bottom)`.
+pub fn bisect_valley<T>(a: &[T], x: &T) -> (Option<usize>, Option<usize>)
+where
+    T: PartialOrd,
+{
+    if a.is_empty() {
+        return (None, None);
+    }
+    let bottom = search(0, a.len() - 1, |i| a[i] > a[i + 1]);
+
+    let descending = search(0, bottom + 1, |i| a[i] > *x);
+    let ascending = bisect_left_slice(a, x, bottom..);
+
+    let is_match = |i: usize| a.get(i) == Some(x);
+    (
+        Some(descending).filter(|i| *i <= bottom && is_match(*i)),
+        Some(ascending).filter(|i| is_match(*i)),
+    )
+}
+
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, and return the index it was
+/// inserted at.
+///
+/// If `a` contains `x`, the insertion point cycles through the run of elements equal to `x`,
+/// rather than always being at one end of it: the position is `rotor` modulo the number of
+/// possible positions (one more than the length of the run), and `rotor` is advanced on each
+/// call. Any position in the run keeps `a` sorted.
+pub fn insort_balanced<T>(a: &mut Vec<T>, x: T, rotor: &mut usize) -> usize
+where
+    T: Ord,
+{
+    let lo = bisect_left(a, &x);
+    let hi = bisect_right_slice(a, &x, lo..);
+    let i = lo + *rotor % (hi - lo + 1);
+    *rotor = rotor.wrapping_add(1);
+    a.insert(i, x);
+    i
+}
+
+/// Return the fractional position of `x` in `a`, assuming `a` is sorted, for linearly
+/// interpolating a parallel array.
+///
+/// If `a[i] <= x < a[i + 1]`, the result is `i + (x - a[i]) / (a[i + 1] - a[i])`, so `x` halfway
+/// between `a[3]` and `a[4]` gives `3.5`, and `x == a[i]` gives `i` (or the index of the
+/// *rightmost* equal element, for duplicates). Results are clamped to `0.0` below `a[0]` and to
+/// `(a.len() - 1) as f64` above the last element. An empty slice gives `0.0`, and a NaN `x`
+/// gives NaN, since it has no position.
+pub fn bisect_fractional(a: &[f64], x: f64) -> f64 {
+    let last = match a.len().checked_sub(1) {
+        Some(last) => last,
+        None => return 0.0,
+    };
+    if x.is_nan() {
+        return f64::NAN;
+    }
+    if x < a[0] {
+        return 0.0;
+    }
+    if x >= a[last] {
+        return last as f64;
+    }
+    let i = bisect_right(a, &x) - 1;
+    i as f64 + (x - a[i]) / (a[i + 1] - a[i])
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, along with a
+/// human-readable trace of the search.
+///
+/// The index is the same as [`bisect_left`]. The trace has one line per probe, showing the
+/// `lo`, `mid` and `hi` of the current bracket, the probed element and which half the search
+/// continued in, e.g. `lo=0 mid=3 hi=7 a[mid]=4 < x -> right`.
+#[cfg(feature = "trace")]
+pub fn bisect_left_trace<T>(a: &[T], x: &T) -> (usize, String)
+where
+    T: PartialOrd + std::fmt::Debug,
+{
+    use std::fmt::Write;
+
+    let mut trace = String::new();
+    let (mut lo, mut hi) = (0, a.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let (outcome, lo_next, hi_next) = if a[mid] < *x {
+            ("< x -> right", mid + 1, hi)
+        } else {
+            (">= x -> left", lo, mid)
+        };
+        writeln!(
+            trace,
+            "lo={} mid={} hi={} a[mid]={:?} {}",
+            lo, mid, hi, a[mid], outcome
+        )
+        .expect("writing to a String cannot fail");
+        lo = lo_next;
+        hi = hi_next;
+    }
+    (lo, trace)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, using a custom
+/// midpoint function.
+///
+/// `mid(lo, hi)` chooses the element to probe in the bracket `lo..hi`. The result is the same as
+/// [`bisect_left`] for any valid midpoint function; only the number of probes changes.
+///
+/// # Panics
+///
+/// Panics if `mid` returns a value outside `lo..hi`, which would otherwise never terminate.
+pub fn bisect_left_with_mid<T, M>(a: &[T], x: &T, mid: M) -> usize
+where
+    T: PartialOrd,
+    M: Fn(usize, usize) -> usize,
+{
+    let (mut lo, mut hi) = (0, a.len());
+    while lo < hi {
+        let m = mid(lo, hi);
+        assert!(
+            lo <= m && m < hi,
+            "midpoint {} is outside of {}..{}",
+            m,
+            lo,
+            hi
+        );
+        if a[m] < *x {
+            lo = m + 1;
+        } else {
+            hi = m;
+        }
+    }
+    lo
+}
+
+/// Return the index where `x` should be inserted in `a`, comparing `x` against each probed
+/// element transformed by `map`.
+///
+/// `map` must be monotone non-decreasing over `a`, i.e. `a` must remain sorted after mapping
+/// every element, otherwise the result is meaningless. The result is the same as [`bisect_left`]
+/// on the mapped slice, without computing it.
+pub fn bisect_left_mapped<T, U, M>(a: &[T], x: &U, map: M) -> usize
+where
+    U: PartialOrd,
+    M: Fn(&T) -> U,
+{
+    search(0, a.len(), |mid| map(&a[mid]) < *x)
+}
+
+/// An error from a checked search over floating point values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatError {
+    /// The target, or an element compared against it, was `NaN`.
+    Nan,
+}
+
+impl fmt::Display for FloatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FloatError::Nan => write!(f, "NaN encountered in float comparison"),
+        }
+    }
+}
+
+impl Error for FloatError {}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, or an error if
+/// a `NaN` is encountered.
+///
+/// The index is the same as [`bisect_left`]. Returns `Err(FloatError::Nan)` if `x` is `NaN` or
+/// any element probed during the search is `NaN`, since `a` can't then be totally ordered and
+/// the index would be meaningless. Elements that aren't probed are not checked.
+pub fn try_bisect_left_f64(a: &[f64], x: f64) -> Result<usize, FloatError> {
+    if x.is_nan() {
+        return Err(FloatError::Nan);
+    }
+    let mut nan = false;
+    let i = search(0, a.len(), |mid| {
+        nan |= a[mid].is_nan();
+        a[mid] < x
+    });
+    if nan {
+        Err(FloatError::Nan)
+    } else {
+        Ok(i)
+    }
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted.
+///
+/// This is [`bisect_left`] accepting anything that can be viewed as a slice, such as `Vec<T>`,
+/// `Box<[T]>`, `Arc<[T]>` or `Cow<[T]>`, owned or borrowed.
+pub fn bisect_left_asref<A, T>(a: A, x: &T) -> usize
+where
+    A: AsRef<[T]>,
+    T: PartialOrd,
+{
+    bisect_left(a.as_ref(), x)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted.
+///
+/// This is [`bisect_right`] accepting anything that can be viewed as a slice, such as `Vec<T>`,
+/// `Box<[T]>`, `Arc<[T]>` or `Cow<[T]>`, owned or borrowed.
+pub fn bisect_right_asref<A, T>(a: A, x: &T) -> usize
+where
+    A: AsRef<[T]>,
+    T: PartialOrd,
+{
+    bisect_right(a.as_ref(), x)
+}
+
+/// Insert the entry `(ts, v)` in `a`, keeping it sorted by timestamp assuming `a` is sorted by
+/// timestamp, then evict and return every entry older than `window` relative to the newest.
+///
+/// If `a` contains entries with timestamp `ts`, insert just *after* the *rightmost* of them.
+/// After inserting, all entries with a timestamp `< newest - window` are removed, where `newest`
+/// is the largest timestamp in `a`, so an out-of-order insert never evicts anything by itself
+/// unless it is already outside the window. Evicted entries are returned oldest first.
+pub fn insort_windowed_right<V>(
+    a: &mut Vec<(u64, V)>,
+    ts: u64,
+    v: V,
+    window: u64,
+) -> Vec<(u64, V)> {
+    let i = bisect_right_by(a, |e| ts.cmp(&e.0));
+    a.insert(i, (ts, v));
+
+    let newest = a[a.len() - 1].0;
+    let cutoff = newest.saturating_sub(window);
+    let evict = bisect_left_by(a, |e| e.0.cmp(&cutoff));
+    a.drain(..evict).collect()
+}
+
+/// Return the range of elements of `a` equal to `x`, assuming `a` is sorted.
+///
+/// The range is `bisect_left(a, x)..bisect_right(a, x)`, and is empty (starting at the insertion
+/// point) if `a` doesn't contain `x`. An element that is neither less nor greater than `x` counts
+/// as equal, so an `x` that compares with nothing, e.g. a NaN, gives `0..a.len()`, as those two
+/// calls do.
+pub fn equal_range<T>(a: &[T], x: &T) -> Range<usize>
+where
+    T: PartialOrd,
+{
+    equal_range_by(a, |e| {
+        if *e < *x {
+            Ordering::Less
+        } else if *x < *e {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    })
+}
+
+/// Return the range of elements of `a` for which `f` returns `Equal`, assuming `a` is sorted,
+/// according to a comparator function.
+///
+/// `f` follows the same convention as [`bisect_left_by`], returning how each element compares to
+/// the target. Both ends of the range are found with a single descent until the first `Equal`
+/// probe, after which the search forks into the two halves.
+pub fn equal_range_by<T, F>(a: &[T], mut f: F) -> Range<usize>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let (mut lo, mut hi) = (0, a.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match f(&a[mid]) {
+            Ordering::Less => lo = mid + 1,
+            Ordering::Greater => hi = mid,
+            Ordering::Equal => {
+                let left = search(lo, mid, |i| f(&a[i]) == Ordering::Less);
+                let right = search(mid + 1, hi, |i| f(&a[i]) != Ordering::Greater);
+                return left..right;
+            }
+        }
+    }
+    lo..lo
+}
+
+/// Return the index of the first element equal to `x` in `a` along with the run of all such
+/// elements, assuming `a` is sorted, or `None` if `a` doesn't contain `x`.
+pub fn find_run<'a, T>(a: &'a [T], x: &T) -> Option<(usize, &'a [T])>
+where
+    T: PartialOrd,
+{
+    let range = equal_range(a, x);
+    if range.is_empty() {
+        None
+    } else {
+        Some((range.start, &a[range]))
+    }
+}
+
+/// Return the index of the first element of `a` for which `f` returns `Equal` along with the run
+/// of all such elements, assuming `a` is sorted, or `None` if there are none.
+///
+/// `f` follows the same convention as [`bisect_left_by`].
+pub fn find_run_by<T, F>(a: &[T], f: F) -> Option<(usize, &[T])>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let range = equal_range_by(a, f);
+    if range.is_empty() {
+        None
+    } else {
+        Some((range.start, &a[range]))
+    }
+}
+
+/// Remove duplicates from `a` in place, assuming `a` is sorted, and return the number of
+/// occurences of each remaining value.
+///
+/// The counts are parallel to the deduplicated `a`: `counts[i]` is the number of times `a[i]`
+/// appeared originally. The length of each run is found by bisecting for its end, so counting
+/// takes `O(distinct * log n)` comparisons.
+pub fn dedup_sorted_counted<T>(a: &mut Vec<T>) -> Vec<usize>
+where
+    T: Ord,
+{
+    let mut counts = vec![];
+    let mut start = 0;
+    while start < a.len() {
+        let end = bisect_right_slice(a, &a[start], start..);
+        counts.push(end - start);
+        start = end;
+    }
+    a.dedup();
+    counts
+}
+
+/// Return the original row index of the *leftmost* key equal to `x`, assuming `keys` is sorted,
+/// or `None` if `keys` doesn't contain `x`.
+///
+/// `perm[i]` is the original (pre-sort) row index of `keys[i]`, as produced by an argsort.
+///
+/// # Panics
+///
+/// Panics if `perm` is shorter than `keys` and a match is found past its end.
+pub fn bisect_left_with_perm<K>(keys: &[K], perm: &[usize], x: &K) -> Option<usize>
+where
+    K: PartialOrd,
+{
+    let i = bisect_left(keys, x);
+    if keys.get(i) == Some(x) {
+        Some(perm[i])
+    } else {
+        None
+    }
+}
+
+/// Return the index of *any* element of `a` for which `f` returns `Equal`, assuming `a` is
+/// sorted, or `None` if there is no such element.
+///
+/// `f` follows the same convention as [`bisect_left_by`]. Unlike [`bisect_left_by`], the search
+/// stops at the first probe that returns `Equal` instead of continuing to the boundary of the
+/// equal run, which saves comparisons when they're expensive and any match will do.
+pub fn bisect_any_by<T, F>(a: &[T], mut f: F) -> Option<usize>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let (mut lo, mut hi) = (0, a.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match f(&a[mid]) {
+            Ordering::Less => lo = mid + 1,
+            Ordering::Greater => hi = mid,
+            Ordering::Equal => return Some(mid),
+        }
+    }
+    None
+}
+
+/// Return the range of elements of `a` within `epsilon` of `center`, assuming `a` is sorted.
+///
+/// The range covers every element in the closed band `[center - epsilon, center + epsilon]`, so
+/// an `epsilon` of `0.0` gives the same range as [`equal_range`]. If no element is in the band,
+/// the range is empty and starts at the band's insertion point.
+pub fn tolerance_range(a: &[f64], center: f64, epsilon: f64) -> Range<usize> {
+    let lo = bisect_left(a, &(center - epsilon));
+    let hi = bisect_right_slice(a, &(center + epsilon), lo..);
+    lo..hi
+}
+
+/// Insert `x` in the first `len` elements of `buf`, keeping them sorted assuming they are sorted,
+/// and return the index it was inserted at.
+///
+/// This is [`insort_right`] for a fixed-capacity buffer without any allocation: `buf[..len]`
+/// holds the sorted values and anything after is unused. On success `len` is incremented. If the
+/// buffer is already full, `x` is handed back as `Err(x)` and nothing is modified.
+///
+/// # Panics
+///
+/// Panics if `len > N`.
+pub fn insort_right_fixed<T, const N: usize>(
+    buf: &mut [T; N],
+    len: &mut usize,
+    x: T,
+) -> Result<usize, T>
+where
+    T: Ord,
+{
+    assert!(*len <= N, "length {} exceeds capacity {}", *len, N);
+    if *len == N {
+        return Err(x);
+    }
+    let i = bisect_right(&buf[..*len], &x);
+    buf[*len] = x;
+    buf[i..=*len].rotate_right(1);
+    *len += 1;
+    Ok(i)
+}
+
+/// Return the start index and length of the longest run of equal elements in `a`, assuming `a`
+/// is sorted, or `None` if `a` is empty.
+///
+/// The first element of the run is the mode of `a`. Ties are broken by the lowest start index.
+/// Each run is skipped by bisecting for its end, so this takes `O(distinct * log n)` comparisons,
+/// which is much faster than a linear scan when there are few distinct values.
+pub fn mode<T>(a: &[T]) -> Option<(usize, usize)>
+where
+    T: PartialOrd,
+{
+    if a.is_empty() {
+        return None;
+    }
+    let mut best = (0, 0);
+    let mut start = 0;
+    while start < a.len() {
+        let end = bisect_right_slice(a, &a[start], start..);
+        if end - start > best.1 {
+            best = (start, end - start);
+        }
+        start = end;
+    }
+    Some(best)
+}
+
+/// Return the indices `(i, i + 1)` of the neighbouring elements bracketing `x` in `a`, assuming
+/// `a` is sorted, or `None` if `x` is outside `a[0]..=a[a.len() - 1]` or unordered against it,
+/// such as a NaN.
+///
+/// The bracket satisfies `a[i] <= x <= a[i + 1]`. If `x` is equal to an element, `i` is the
+/// index of the *rightmost* such element, so the bracket starts at `x`, except for the last
+/// element where the bracket ends at it instead. Slices with fewer than two elements never have a
+/// bracket.
+pub fn bracket<T>(a: &[T], x: &T) -> Option<(usize, usize)>
+where
+    T: PartialOrd,
+{
+    if a.len() < 2 {
+        return None;
+    }
+    let above_first = matches!(
+        x.partial_cmp(&a[0]),
+        Some(Ordering::Greater | Ordering::Equal)
+    );
+    let below_last = matches!(
+        x.partial_cmp(&a[a.len() - 1]),
+        Some(Ordering::Less | Ordering::Equal)
+    );
+    if !(above_first && below_last) {
+        return None;
+    }
+    let i = std::cmp::min(bisect_right(a, x) - 1, a.len() - 2);
+    Some((i, i + 1))
+}
+
+/// Return an iterator over the distinct values of `a` and the index of their first occurence,
+/// assuming `a` is sorted.
+///
+/// Each run of equal elements is skipped with an exponential search from its start, so a run of
+/// length `k` costs `O(log k)` comparisons rather than `O(k)`.
+pub fn distinct_iter<'a, T>(a: &'a [T]) -> impl Iterator<Item = (usize, &'a T)> + 'a
+where
+    T: PartialOrd,
+{
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        let x = a.get(start)?;
+        let i = start;
+        start = gallop(start + 1, a.len(), |mid| {
+            x.partial_cmp(&a[mid]) != Some(Ordering::Less)
+        });
+        Some((i, x))
+    })
+}
+
+/// Insert `x` into a sorted structure of length `len` accessed only through callbacks, and return
+/// the index it was inserted at.
+///
+/// `cmp(i)` must return how `x` compares to the element at index `i`, and `insert(i, x)` must
+/// insert `x` at index `i`. The index is found by bisecting `0..len`, so `cmp` is called
+/// `O(log len)` times, then `insert` is called once. If the structure contains `x`, it is
+/// inserted just *after* the *rightmost* occurence of `x`.
+pub fn insort_callback<T, C, I>(x: T, len: usize, mut cmp: C, insert: I) -> usize
+where
+    C: FnMut(usize) -> Ordering,
+    I: FnOnce(usize, T),
+{
+    let i = search(0, len, |mid| cmp(mid) != Ordering::Less);
+    insert(i, x);
+    i
+}
+
+/// A vector kept sorted according to a comparator stored alongside it.
+///
+/// Every method uses the same stored comparator, so inserts and searches can't accidentally be
+/// made with inconsistent orderings.
+#[derive(Clone)]
+pub struct SortedVecBy<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    values: Vec<T>,
+    cmp: F,
+}
+
+impl<T, F> SortedVecBy<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    /// Create an empty `SortedVecBy` ordered by `cmp`.
+    pub fn new(cmp: F) -> Self {
+        SortedVecBy {
+            values: Vec::new(),
+            cmp,
+        }
+    }
+
+    /// Create a `SortedVecBy` ordered by `cmp` from the values in `values`, sorting them first.
+    pub fn from_vec(mut values: Vec<T>, cmp: F) -> Self {
+        values.sort_by(&cmp);
+        SortedVecBy { values, cmp }
+    }
+
+    /// Insert `x`, keeping the values sorted, and return the index it was inserted at.
+    ///
+    /// If equal values are present, insert just *after* the *rightmost* of them.
+    pub fn insert(&mut self, x: T) -> usize {
+        let cmp = &self.cmp;
+        let i = search(0, self.values.len(), |mid| {
+            cmp(&self.values[mid], &x) != Ordering::Greater
+        });
+        self.values.insert(i, x);
+        i
+    }
+
+    /// Return the index of the *leftmost* value equal to `x`, or `None` if there is none.
+    pub fn find(&self, x: &T) -> Option<usize> {
+        let i = self.lower_bound(x);
+        match self.values.get(i) {
+            Some(e) if (self.cmp)(e, x) == Ordering::Equal => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Return `true` if a value equal to `x` is present.
+    pub fn contains(&self, x: &T) -> bool {
+        self.find(x).is_some()
+    }
+
+    /// Return the values in the half-open interval `[low, high)`.
+    ///
+    /// The slice is empty if `low` is not before `high`.
+    pub fn range(&self, low: &T, high: &T) -> &[T] {
+        let lo = self.lower_bound(low);
+        let hi = std::cmp::max(lo, self.lower_bound(high));
+        &self.values[lo..hi]
+    }
+
+    /// Return the first value that is greater than or equal to `x`, if any.
+    pub fn first_ge(&self, x: &T) -> Option<&T> {
+        self.values.get(self.lower_bound(x))
+    }
+
+    /// Return the first value that is strictly greater than `x`, if any.
+    pub fn first_gt(&self, x: &T) -> Option<&T> {
+        self.values.get(self.upper_bound(x))
+    }
+
+    /// Remove and return the *leftmost* value equal to `x`, if any.
+    pub fn remove(&mut self, x: &T) -> Option<T> {
+        self.find(x).map(|i| self.values.remove(i))
+    }
+
+    /// Return the number of values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Return `true` if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Return the values, in sorted order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Return the underlying vector, in sorted order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.values
+    }
+
+    fn lower_bound(&self, x: &T) -> usize {
+        search(0, self.values.len(), |mid| {
+            (self.cmp)(&self.values[mid], x) == Ordering::Less
+        })
+    }
+
+    fn upper_bound(&self, x: &T) -> usize {
+        search(0, self.values.len(), |mid| {
+            (self.cmp)(&self.values[mid], x) != Ordering::Greater
+        })
+    }
+}
+
+impl<T, F> fmt::Debug for SortedVecBy<T, F>
+where
+    T: fmt::Debug,
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortedVecBy")
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+/// The next step of a [`BisectState`] search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BisectStep {
+    /// Compare the target against the element at this index and [`feed`](BisectState::feed) the
+    /// result.
+    Probe(usize),
+    /// The search has finished with this insertion index.
+    Done(usize),
+}
+
+/// An incremental, left-biased bisection driven by the caller one comparison at a time.
+///
+/// This allows the comparisons to be made asynchronously, e.g. across `.await` points when
+/// fetching elements from remote storage. The caller compares the target against the element at
+/// [`probe_index`](BisectState::probe_index) and feeds the result back until the search is done:
+///
+/// ```
+/// use bisection::{BisectState, BisectStep};
+///
+/// let a = [1, 3, 5, 7];
+/// let mut state = BisectState::start(a.len());
+/// let index = loop {
+///     match state.step() {
+///         BisectStep::Probe(i) => state.feed(a[i].cmp(&5)),
+///         BisectStep::Done(i) => break i,
+///     };
+/// };
+/// assert_eq!(index, 2);
+/// ```
+///
+/// The orderings fed follow the same convention as [`bisect_left_by`], so the result is the same
+/// as `bisect_left_by` would give, after at most `⌈log2(len + 1)⌉` probes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BisectState {
+    lo: usize,
+    hi: usize,
+}
+
+impl BisectState {
+    /// Start a search over `0..len`.
+    pub fn start(len: usize) -> Self {
+        BisectState { lo: 0, hi: len }
+    }
+
+    /// Return the index to probe next, or `None` if the search has finished.
+    pub fn probe_index(&self) -> Option<usize> {
+        if self.lo < self.hi {
+            Some(self.lo + (self.hi - self.lo) / 2)
+        } else {
+            None
+        }
+    }
+
+    /// Return the next step of the search.
+    pub fn step(&self) -> BisectStep {
+        match self.probe_index() {
+            Some(i) => BisectStep::Probe(i),
+            None => BisectStep::Done(self.lo),
+        }
+    }
+
+    /// Supply how the element at [`probe_index`](BisectState::probe_index) compares to the
+    /// target, and return the next step.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the search has already finished.
+    pub fn feed(&mut self, ordering: Ordering) -> BisectStep {
+        let mid = self
+            .probe_index()
+            .expect("fed a comparison to a finished search");
+        if ordering == Ordering::Less {
+            self.lo = mid + 1;
+        } else {
+            self.hi = mid;
+        }
+        self.step()
+    }
+}
+
+/// Return the number of elements of `a` in the half-open interval `[low, high)`, assuming `a` is
+/// sorted.
+///
+/// Returns 0 if the interval is empty or inverted.
+pub fn count_in_range<T>(a: &[T], low: &T, high: &T) -> usize
+where
+    T: PartialOrd,
+{
+    bisect_left(a, high).saturating_sub(bisect_left(a, low))
+}
+
+/// Return the number of elements of `a` in the closed interval `[low, high]`, assuming `a` is
+/// sorted.
+///
+/// Returns 0 if the interval is empty or inverted.
+pub fn count_in_range_inclusive<T>(a: &[T], low: &T, high: &T) -> usize
+where
+    T: PartialOrd,
+{
+    bisect_right(a, high).saturating_sub(bisect_left(a, low))
+}
+
+/// Return the number of elements of `a` in the open interval `(low, high)`, assuming `a` is
+/// sorted.
+///
+/// Returns 0 if the interval is empty or inverted.
+pub fn count_in_range_exclusive<T>(a: &[T], low: &T, high: &T) -> usize
+where
+    T: PartialOrd,
+{
+    bisect_left(a, high).saturating_sub(bisect_right(a, low))
+}
+
+/// Return the number of elements of `a` in the half-open interval `(low, high]`, assuming `a` is
+/// sorted.
+///
+/// Returns 0 if the interval is empty or inverted.
+pub fn count_in_range_left_open<T>(a: &[T], low: &T, high: &T) -> usize
+where
+    T: PartialOrd,
+{
+    bisect_right(a, high).saturating_sub(bisect_right(a, low))
+}
+
+/// Return the index where `x` should be inserted in each of the `segments` of `a`, assuming each
+/// segment is sorted.
+///
+/// `a` as a whole needn't be sorted. The result has one global index into `a` per segment, in
+/// the same order as `segments`, as given by `bisect_left_slice(a, x, segment)`.
+///
+/// # Panics
+///
+/// Panics if a segment is out of bounds of `a`, or if the segments are not in ascending order
+/// and non-overlapping.
+pub fn bisect_left_segmented<T>(a: &[T], segments: &[Range<usize>], x: &T) -> Vec<usize>
+where
+    T: PartialOrd,
+{
+    for pair in segments.windows(2) {
+        assert!(
+            pair[0].end <= pair[1].start,
+            "segments {:?} and {:?} overlap or are out of order",
+            pair[0],
+            pair[1]
+        );
+    }
+    segments
+        .iter()
+        .map(|segment| {
+            assert!(
+                segment.start <= segment.end && segment.end <= a.len(),
+                "segment {:?} is out of bounds",
+                segment
+            );
+            bisect_left_slice(a, x, segment.clone())
+        })
+        .collect()
+}
+
+/// Return the elements of `a` in the half-open interval `[low, high)`, assuming `a` is sorted.
+///
+/// Returns an empty slice if the interval is empty or inverted.
+pub fn between<'a, T>(a: &'a [T], low: &T, high: &T) -> &'a [T]
+where
+    T: PartialOrd,
+{
+    let lo = bisect_left(a, low);
+    let hi = std::cmp::max(lo, bisect_left(a, high));
+    &a[lo..hi]
+}
+
+/// Return the elements of `a` in the closed interval `[low, high]`, assuming `a` is sorted.
+///
+/// Returns an empty slice if the interval is inverted.
+pub fn between_incl<'a, T>(a: &'a [T], low: &T, high: &T) -> &'a [T]
+where
+    T: PartialOrd,
+{
+    let lo = bisect_left(a, low);
+    let hi = std::cmp::max(lo, bisect_right(a, high));
+    &a[lo..hi]
+}
+
+/// Return the inner join of `left` and `right` on their keys, assuming both are sorted by key.
+///
+/// For each key present on both sides, every entry on the left with that key is paired with
+/// every entry on the right with that key (the cross product of the two runs), in left-major
+/// order. Keys present on only one side are dropped. The slices are walked with two pointers, so
+/// this takes `O(n + m + output)` time.
+pub fn merge_join<'a, 'b, K, A, B>(
+    left: &'a [(K, A)],
+    right: &'b [(K, B)],
+) -> Vec<(K, &'a A, &'b B)>
+where
+    K: Ord + Clone,
+{
+    let mut joined = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        match left[i].0.cmp(&right[j].0) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                let key = &left[i].0;
+                let left_end = i + left[i..].iter().take_while(|e| e.0 == *key).count();
+                let right_end = j + right[j..].iter().take_while(|e| e.0 == *key).count();
+                for (_, a) in &left[i..left_end] {
+                    for (_, b) in &right[j..right_end] {
+                        joined.push((key.clone(), a, b));
+                    }
+                }
+                i = left_end;
+                j = right_end;
+            }
+        }
+    }
+    joined
+}
+
+/// An insertion point returned by [`locate_left_typed`] or [`locate_right_typed`].
+///
+/// An insertion point `i` sits *between* elements: just after `a[i - 1]` and just before `a[i]`.
+/// It deliberately doesn't implement arithmetic, so converting it to an element index has to go
+/// through an accessor that makes the intent explicit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InsertionPoint(usize);
+
+impl InsertionPoint {
+    /// Return the raw insertion index, suitable for `Vec::insert` or splitting a slice.
+    pub fn as_index(self) -> usize {
+        self.0
+    }
+
+    /// Return the index of the element just before the insertion point, or `None` if it's at the
+    /// start.
+    pub fn predecessor(self) -> Option<usize> {
+        self.0.checked_sub(1)
+    }
+
+    /// Return the index of the element just after the insertion point in a slice of length
+    /// `len`, or `None` if it's at the end.
+    pub fn successor(self, len: usize) -> Option<usize> {
+        if self.0 < len {
+            Some(self.0)
+        } else {
+            None
+        }
+    }
+
+    /// Return the insertion index, clamped to be at most `len`.
+    pub fn clamp_to(self, len: usize) -> usize {
+        std::cmp::min(self.0, len)
+    }
+}
+
+/// Return the [`InsertionPoint`] where `x` should be inserted in `a`, assuming `a` is sorted.
+///
+/// This is [`bisect_left`] with a typed result.
+pub fn locate_left_typed<T>(a: &[T], x: &T) -> InsertionPoint
+where
+    T: PartialOrd,
+{
+    InsertionPoint(bisect_left(a, x))
+}
+
+/// Return the [`InsertionPoint`] where `x` should be inserted in `a`, assuming `a` is sorted.
+///
+/// This is [`bisect_right`] with a typed result.
+pub fn locate_right_typed<T>(a: &[T], x: &T) -> InsertionPoint
+where
+    T: PartialOrd,
+{
+    InsertionPoint(bisect_right(a, x))
+}
+
+/// Return the position in `index` of the *leftmost* row of `a` whose `field` equals `target`,
+/// or `None` if there is no such row.
+///
+/// `index` is a secondary index over `a`: a permutation of row indices such that
+/// `index.iter().map(|&i| field(&a[i]))` is sorted. Several indices sorted by different fields
+/// can be kept over the same backing `a`. The row itself is `a[index[position]]`.
+pub fn bisect_left_field<T, K, F>(a: &[T], index: &[usize], field: F, target: &K) -> Option<usize>
+where
+    K: PartialOrd,
+    F: Fn(&T) -> K,
+{
+    let i = search(0, index.len(), |mid| field(&a[index[mid]]) < *target);
+    match index.get(i) {
+        Some(row) if field(&a[*row]) == *target => Some(i),
+        _ => None,
+    }
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, along with the
+/// sizes of the two partitions it creates.
+///
+/// The result is `(i, left, right)` where `i` is the same as [`bisect_left`], `left == i` is
+/// the number of elements `< x` and `right == a.len() - i` the number of elements `>= x`.
+pub fn bisect_left_verbose<T>(a: &[T], x: &T) -> (usize, usize, usize)
+where
+    T: PartialOrd,
+{
+    let i = bisect_left(a, x);
+    (i, i, a.len() - i)
+}
+
+/// Return the index of the first element of `ring` that is `>= key`, wrapping around to 0 if
+/// there is none, assuming `ring` is sorted.
+///
+/// This is the node lookup of a consistent-hashing ring: `ring` holds the sorted node hashes and
+/// a key is owned by the first node at or after its hash, with keys beyond the largest node
+/// hash owned by the first node.
+///
+/// # Panics
+///
+/// Panics if `ring` is empty, since there is no node to return.
+pub fn bisect_ring<T>(ring: &[T], key: &T) -> usize
+where
+    T: Ord,
+{
+    assert!(!ring.is_empty(), "cannot look up a key in an empty ring");
+    bisect_left(ring, key) % ring.len()
+}
+
+/// Insert `new_row` in `index`, a sorted index over `data`, keeping it sorted.
+///
+/// `index` holds row indices into `data` such that traversing `data` through `index` yields
+/// sorted values. `data` itself is never reordered, so it can be append-only. If `data` contains
+/// values equal to `data[new_row]`, it is inserted just *after* the *rightmost* of them.
+///
+/// # Panics
+///
+/// Panics if `new_row` or any row in `index` is out of bounds of `data`.
+pub fn insort_index_right<T>(data: &[T], index: &mut Vec<usize>, new_row: usize)
+where
+    T: Ord,
+{
+    let x = &data[new_row];
+    let i = search(0, index.len(), |mid| {
+        data[index[mid]].cmp(x) != Ordering::Greater
+    });
+    index.insert(i, new_row);
+}
+
+/// Return the index where each of `targets` should be inserted in `a`, assuming both `a` and
+/// `targets` are sorted.
+///
+/// The result is the same as mapping [`bisect_right`] over `targets`, but since the insertion
+/// points are non-decreasing, they're found by walking `a` once with a cursor, in `O(n + m)`
+/// comparisons rather than `O(m log n)`. This is faster when there are many targets relative to
+/// the length of `a`.
+pub fn bisect_right_sorted_targets<T>(a: &[T], targets: &[T]) -> Vec<usize>
+where
+    T: PartialOrd,
+{
+    debug_assert!(
+        targets.windows(2).all(|w| w[0] <= w[1]),
+        "targets must be sorted"
+    );
+    let mut i = 0;
+    targets
+        .iter()
+        .map(|x| {
+            while i < a.len() && x.partial_cmp(&a[i]) != Some(Ordering::Less) {
+                i += 1;
+            }
+            i
+        })
+        .collect()
+}
+
+/// Return the range of elements equal to `x` in `a`, assuming `a` is sorted, or `None` if `a`
+/// doesn't contain `x`.
+///
+/// Unlike [`equal_range`], an absent value isn't reported as an empty range, so the result can
+/// be matched on directly before operating on the matching elements.
+pub fn find_range<T>(a: &[T], x: &T) -> Option<Range<usize>>
+where
+    T: PartialOrd,
+{
+    let range = equal_range(a, x);
+    if range.is_empty() {
+        None
+    } else {
+        Some(range)
+    }
+}
+
+/// Return the range of elements of `a` for which `f` returns `Equal`, assuming `a` is sorted, or
+/// `None` if there are none.
+///
+/// `f` follows the same convention as [`bisect_left_by`].
+pub fn find_range_by<T, F>(a: &[T], f: F) -> Option<Range<usize>>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let range = equal_range_by(a, f);
+    if range.is_empty() {
+        None
+    } else {
+        Some(range)
+    }
+}
+
+/// Return the index of the first element of `a` whose variant comes at or after `variant`,
+/// assuming `a` is sorted by variant.
+///
+/// [`std::mem::Discriminant`] isn't ordered, so `order` maps each element to the position of its
+/// variant in the declaration order, and `variant` is the position to search for. This avoids
+/// building a full value of the variant just to compare against it.
+///
+/// # Examples
+///
+/// ```
+/// use bisection::bisect_left_variant;
+///
+/// #[derive(Debug, PartialEq, PartialOrd)]
+/// enum Event {
+///     Start(u32),
+///     Data(String),
+///     End,
+/// }
+///
+/// fn order(e: &Event) -> usize {
+///     match e {
+///         Event::Start(_) => 0,
+///         Event::Data(_) => 1,
+///         Event::End => 2,
+///     }
+/// }
+///
+/// let events = [
+///     Event::Start(3),
+///     Event::Data("a".to_string()),
+///     Event::Data("b".to_string()),
+///     Event::End,
+/// ];
+/// let first_data = bisect_left_variant(&events, order, order(&Event::Data(String::new())));
+/// assert_eq!(first_data, 1);
+/// assert_eq!(bisect_left_variant(&events, order, 2), 3);
+/// ```
+pub fn bisect_left_variant<T, F>(a: &[T], mut order: F, variant: usize) -> usize
+where
+    F: FnMut(&T) -> usize,
+{
+    bisect_left_by(a, |e| order(e).cmp(&variant))
+}
+
+/// A cached position in a sorted vector, for workloads that interleave searches and insertions
+/// close to each other, such as edits around a caret.
+///
+/// Searches gallop outward from the cached position, so a search `k` elements away takes
+/// `O(log k)` comparisons rather than `O(log n)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cursor {
+    index: usize,
+}
+
+impl Cursor {
+    /// Create a cursor at `index`.
+    pub fn new(index: usize) -> Self {
+        Cursor { index }
+    }
+
+    /// Return the cached position.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, and move the
+    /// cursor there.
+    ///
+    /// The result is the same as [`bisect_left`].
+    pub fn search_near<T>(&mut self, a: &[T], x: &T) -> usize
+    where
+        T: PartialOrd,
+    {
+        let start = std::cmp::min(self.index, a.len());
+        let go_right = |i: usize| a[i] < *x;
+        self.index = if start == 0 || go_right(start - 1) {
+            gallop(start, a.len(), go_right)
+        } else {
+            gallop_back(0, start - 1, go_right)
+        };
+        self.index
+    }
+
+    /// Record that an element was inserted in the vector at `index`, shifting the cursor to keep
+    /// it on the same element if the insertion was at or before it.
+    pub fn inserted_at(&mut self, index: usize) {
+        if index <= self.index {
+            self.index += 1;
+        }
+    }
+
+    /// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, and return its index.
+    ///
+    /// The insertion point is found from the cursor and is the same as [`insort_left`]'s. The
+    /// cursor isn't moved to the insertion point, only shifted as by [`Cursor::inserted_at`].
+    pub fn insort<T>(&mut self, a: &mut Vec<T>, x: T) -> usize
+    where
+        T: PartialOrd,
+    {
+        let mut probe = *self;
+        let index = probe.search_near(a, &x);
+        a.insert(index, x);
+        self.inserted_at(index);
+        index
+    }
+}
+
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, and return its index along with
+/// a copy of the element now just before it, or `None` if it was inserted at the front.
+///
+/// If `a` contains `x`, insert it just *after* the *rightmost* occurence of `x`, so the
+/// predecessor is then equal to `x`. This suits delta encoding, where each value is stored
+/// relative to its predecessor.
+pub fn insort_right_with_prev<T>(a: &mut Vec<T>, x: T) -> (usize, Option<T>)
+where
+    T: Ord + Clone,
+{
+    let index = bisect_right(a, &x);
+    let prev = index.checked_sub(1).map(|i| a[i].clone());
+    a.insert(index, x);
+    (index, prev)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted by absolute
+/// value.
+///
+/// The sign of the stored values is ignored for ordering, so `[0.0, -1.0, 2.0, -3.0]` counts as
+/// sorted, and `x` goes before any element with the same absolute value.
+pub fn bisect_left_abs(a: &[f64], x: f64) -> usize {
+    let x = x.abs();
+    search(0, a.len(), |mid| a[mid].abs() < x)
+}
+
+/// Return `(bisect_left(a, x), bisect_right(a, x))`, assuming `a` is sorted.
+///
+/// This is [`equal_range`] as a tuple: both bounds come from a single descent that only forks
+/// once it reaches an element equal to `x`, so it's cheaper than calling [`bisect_left`] and
+/// [`bisect_right`] separately.
+pub fn bounds_of<T>(a: &[T], x: &T) -> (usize, usize)
+where
+    T: PartialOrd,
+{
+    let range = equal_range(a, x);
+    (range.start, range.end)
+}
+
+/// What [`bisect_left_slice_policy`] does when the range to search is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyRangePolicy {
+    /// Return the start of the range, as [`bisect_left_slice`] does.
+    ReturnStart,
+    /// Return `None`.
+    ReturnNone,
+    /// Panic.
+    Panic,
+}
+
+/// Return the index where `x` should be inserted in `a[within]`, assuming `a` is sorted, with
+/// `policy` deciding the result when `within` is empty.
+///
+/// For a non-empty range, the result is always `Some` of the same index as
+/// [`bisect_left_slice`].
+///
+/// # Panics
+///
+/// Panics if `within` is out of bounds of `a`, or if it's empty and `policy` is
+/// [`EmptyRangePolicy::Panic`].
+pub fn bisect_left_slice_policy<T, I>(
+    a: &[T],
+    x: &T,
+    within: I,
+    policy: EmptyRangePolicy,
+) -> Option<usize>
+where
+    I: RangeBounds<usize>,
+    T: PartialOrd,
+{
+    let (lo, hi) = bounds_to_indices(a, within);
+    if lo >= hi {
+        match policy {
+            EmptyRangePolicy::ReturnStart => {}
+            EmptyRangePolicy::ReturnNone => return None,
+            EmptyRangePolicy::Panic => panic!("empty search range"),
+        }
+    }
+    Some(search(lo, hi, |mid| a[mid] < *x))
+}
+
+/// Return the index of the interval in `intervals` containing `p`, or `None` if there is none.
+///
+/// `intervals` are half-open `(start, end)` pairs, and are assumed to be sorted by `start` and
+/// not to overlap, so at most one can contain `p`: the last one starting at or before it.
+pub fn stab<T>(intervals: &[(T, T)], p: &T) -> Option<usize>
+where
+    T: PartialOrd,
+{
+    let i = search(0, intervals.len(), |mid| {
+        p.partial_cmp(&intervals[mid].0) != Some(Ordering::Less)
+    })
+    .checked_sub(1)?;
+    if *p < intervals[i].1 {
+        Some(i)
+    } else {
+        None
+    }
+}
+
+/// Where [`locate_clamped`] found an insertion point, relative to the searched range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clamp {
+    /// The insertion point is the start of the range, so no searched element is less than the
+    /// target.
+    Below(usize),
+    /// The insertion point is strictly inside the range.
+    Within(usize),
+    /// The insertion point is the end of the range, so every searched element is less than the
+    /// target.
+    Above(usize),
+}
+
+impl Clamp {
+    /// Return the insertion index, wherever it is.
+    pub fn index(self) -> usize {
+        match self {
+            Clamp::Below(i) | Clamp::Within(i) | Clamp::Above(i) => i,
+        }
+    }
+}
+
+/// Return the index where `x` should be inserted in `a[within]`, assuming `a` is sorted, along
+/// with whether it was clamped to either end of `within`.
+///
+/// The index is the same as [`bisect_left_slice`]'s. An empty range gives [`Clamp::Below`].
+///
+/// # Panics
+///
+/// Panics if `within` is out of bounds of `a`.
+pub fn locate_clamped<T, I>(a: &[T], x: &T, within: I) -> Clamp
+where
+    I: RangeBounds<usize>,
+    T: PartialOrd,
+{
+    let (lo, hi) = bounds_to_indices(a, within);
+    let i = search(lo, hi, |mid| a[mid] < *x);
+    if i <= lo {
+        Clamp::Below(i)
+    } else if i == hi {
+        Clamp::Above(i)
+    } else {
+        Clamp::Within(i)
+    }
+}
+
+/// An `f64` with a total order, so slices of floats can be used with every function in this
+/// crate, including those requiring `Ord`.
+///
+/// Numbers are ordered as usual, except that `-0.0` is less than `+0.0`, and NaNs are greater
+/// than every number and equal to each other.
+#[derive(Debug, Clone, Copy)]
+pub struct OrdF64(pub f64);
+
+impl PartialEq for OrdF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrdF64 {}
+
+impl PartialOrd for OrdF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrdF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.0.is_nan(), other.0.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self.0.total_cmp(&other.0),
+        }
+    }
+}
+
+impl From<f64> for OrdF64 {
+    fn from(x: f64) -> Self {
+        OrdF64(x)
+    }
+}
+
+impl From<OrdF64> for f64 {
+    fn from(x: OrdF64) -> Self {
+        x.0
+    }
+}
+
+/// Return the index where `x` should be inserted in `a` as by [`bisect_right`], along with the
+/// element at that index, i.e. the first element greater than `x`.
+pub fn bisect_right_at<'a, T>(a: &'a [T], x: &T) -> (usize, Option<&'a T>)
+where
+    T: PartialOrd,
+{
+    let i = bisect_right(a, x);
+    (i, a.get(i))
+}
+
+/// Return the index where `x` should be inserted in `a` as by [`bisect_left`], along with the
+/// element at that index, i.e. the first element not less than `x`.
+pub fn bisect_left_at<'a, T>(a: &'a [T], x: &T) -> (usize, Option<&'a T>)
+where
+    T: PartialOrd,
+{
+    let i = bisect_left(a, x);
+    (i, a.get(i))
+}
+
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, after every element for which
+/// `by` returns `Equal`.
+///
+/// `by` compares an element of `a` against `x`, following the same convention as
+/// [`bisect_left_by`]. Inserting at the end of the run of equal elements is the stable choice:
+/// elements with equal keys stay in the order they were inserted, as if the whole vector had
+/// been sorted with a stable sort.
+pub fn insort_stable<T, F>(a: &mut Vec<T>, x: T, by: F)
+where
+    F: Fn(&T) -> Ordering,
+{
+    let i = search(0, a.len(), |mid| by(&a[mid]) != Ordering::Greater);
+    a.insert(i, x);
+}
+
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, before every element for which
+/// `by` returns `Equal`.
+///
+/// This is the counterpart to [`insort_stable`], leaving elements with equal keys in the reverse
+/// of the order they were inserted.
+pub fn insort_stable_front<T, F>(a: &mut Vec<T>, x: T, by: F)
+where
+    F: Fn(&T) -> Ordering,
+{
+    let i = search(0, a.len(), |mid| by(&a[mid]) == Ordering::Less);
+    a.insert(i, x);
+}
+
+/// Return the index where `target` should be inserted among `count` fixed-size records read
+/// from `reader`, assuming the records are sorted, as by [`bisect_left_by`].
+///
+/// Record `i` is the `record_size` bytes at offset `i * record_size`, and `cmp` compares a
+/// record against `target`, following the same convention as [`bisect_left_by`]. Each probe
+/// seeks to and reads a single record, so this takes `O(log count)` seeks and never needs the
+/// whole file in memory.
+///
+/// # Errors
+///
+/// Returns any error from seeking or reading, including
+/// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) if a record is cut short, and
+/// [`InvalidInput`](io::ErrorKind::InvalidInput) if a probed record's offset doesn't fit in a
+/// `u64`.
+pub fn bisect_left_seek<R, F>(
+    reader: &mut R,
+    count: usize,
+    record_size: usize,
+    target: &[u8],
+    cmp: F,
+) -> io::Result<usize>
+where
+    R: Read + Seek,
+    F: Fn(&[u8], &[u8]) -> Ordering,
+{
+    let mut record = vec![0; record_size];
+    let (mut lo, mut hi) = (0, count);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let offset = (mid as u64)
+            .checked_mul(record_size as u64)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "record offset overflows")
+            })?;
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut record)?;
+        if cmp(&record, target) == Ordering::Less {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(lo)
+}
+
+/// Return the range of up to `k` elements of `a` centered on the insertion point of `x`,
+/// assuming `a` is sorted.
+///
+/// With `i = bisect_left(a, x)`, the range is `i - k / 2..i - k / 2 + k`, i.e. `k / 2` elements
+/// before the insertion point and the rest, which is one more when `k` is odd, from it onward.
+/// Near either end of `a` the range is shifted to still cover `k` elements, and it is all of `a`
+/// if `k > a.len()`.
+pub fn around<T>(a: &[T], x: &T, k: usize) -> Range<usize>
+where
+    T: PartialOrd,
+{
+    let k = std::cmp::min(k, a.len());
+    let i = bisect_left(a, x);
+    let start = std::cmp::min(i.saturating_sub(k / 2), a.len() - k);
+    start..start + k
+}
+
+/// Return the number of elements of `a` less than `x`, assuming `a` is sorted.
+pub fn count_lt<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    bisect_left(a, x)
+}
+
+/// Return the number of elements of `a` less than or equal to `x`, assuming `a` is sorted.
+pub fn count_le<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    bisect_right(a, x)
+}
+
+/// Return the number of elements of `a` greater than `x`, assuming `a` is sorted.
+pub fn count_gt<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    a.len() - bisect_right(a, x)
+}
+
+/// Return the number of elements of `a` greater than or equal to `x`, assuming `a` is sorted.
+pub fn count_ge<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    a.len() - bisect_left(a, x)
+}
+
+/// Return the number of elements of `a` equal to `x`, assuming `a` is sorted.
+pub fn count_equal<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    equal_range(a, x).len()
+}
+
+/// Return the index where the implicit target should be inserted in `a`, assuming `a` is
+/// sorted, before any elements equal to it.
+///
+/// `cmp_to_target` returns how a probed *element* compares to the target, the same convention
+/// as [`bisect_left_by`]. Unlike the `_by` functions, [`bisect_right_cmp_target`] follows the
+/// same convention too, so one closure can be used for both sides.
+pub fn bisect_left_cmp_target<T, F>(a: &[T], cmp_to_target: F) -> usize
+where
+    F: Fn(&T) -> Ordering,
+{
+    search(0, a.len(), |mid| cmp_to_target(&a[mid]) == Ordering::Less)
+}
+
+/// Return the index where the implicit target should be inserted in `a`, assuming `a` is
+/// sorted, after any elements equal to it.
+///
+/// `cmp_to_target` follows the same convention as for [`bisect_left_cmp_target`].
+pub fn bisect_right_cmp_target<T, F>(a: &[T], cmp_to_target: F) -> usize
+where
+    F: Fn(&T) -> Ordering,
+{
+    search(0, a.len(), |mid| {
+        cmp_to_target(&a[mid]) != Ordering::Greater
+    })
+}
+
+/// Return the position of an element equal to `x` in a `rows` by `cols` matrix, or `None` if
+/// there is none, assuming every row and every column is sorted ascending.
+///
+/// `at(r, c)` returns the element at row `r` and column `c`, so any backing storage can be
+/// searched. The search walks a staircase from the top-right corner, stepping down when the
+/// element is too small and left when it's too large, in `O(rows + cols)` probes.
+///
+/// Elements that can't be compared with `x`, e.g. a NaN, aren't supported. The walk steps left
+/// past one, so an element equal to `x` further down the same column may be missed.
+pub fn bisect_matrix<T, F>(rows: usize, cols: usize, at: F, x: &T) -> Option<(usize, usize)>
+where
+    T: PartialOrd,
+    F: Fn(usize, usize) -> T,
+{
+    let (mut r, mut c) = (0, cols);
+    while r < rows && c > 0 {
+        let e = at(r, c - 1);
+        if e < *x {
+            r += 1;
+        } else if e > *x {
+            c -= 1;
+        } else if e == *x {
+            return Some((r, c - 1));
+        } else {
+            // Incomparable: nothing tells which way `x` lies, so give up on the rest of this
+            // column rather than loop
+            c -= 1;
+        }
+    }
+    None
+}
+
+/// The result of [`insort_right_run_info`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunInfo {
+    /// The index the element was inserted at.
+    pub index: usize,
+    /// Whether the element joined an existing run of equal elements.
+    pub extends_run: bool,
+    /// The length of the element's run of equal elements after the insertion.
+    pub run_len_after: usize,
+}
+
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, and report how it changed the
+/// run of elements equal to `x`.
+///
+/// If `a` contains `x`, insert it just *after* the *rightmost* occurence of `x`.
+pub fn insort_right_run_info<T>(a: &mut Vec<T>, x: T) -> RunInfo
+where
+    T: Ord,
+{
+    let run = equal_range(a, &x);
+    a.insert(run.end, x);
+    RunInfo {
+        index: run.end,
+        extends_run: !run.is_empty(),
+        run_len_after: run.len() + 1,
+    }
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, searching only
+/// among the elements between `value_lo` and `value_hi` inclusive.
+///
+/// The search is narrowed to `bisect_left(a, value_lo)..bisect_right(a, value_hi)` and then
+/// bisects for `x` within it, returning an index into all of `a`. This is the same as
+/// [`bisect_left`] when `x` lies in the band; otherwise the result is clamped to the band's
+/// nearer end.
+pub fn bisect_left_within_values<T>(a: &[T], x: &T, value_lo: &T, value_hi: &T) -> usize
+where
+    T: PartialOrd,
+{
+    let lo = bisect_left(a, value_lo);
+    let hi = std::cmp::max(lo, bisect_right(a, value_hi));
+    bisect_left_slice(a, x, lo..hi)
+}
+
+/// A sorted sequence that can be searched without being held in memory as a slice, e.g. one
+/// backed by external storage or computed by a formula.
+pub trait Indexable {
+    /// The type of the elements.
+    type Item;
+
+    /// Return the number of elements.
+    fn len(&self) -> usize;
+
+    /// Return the element at index `i`, where `i < self.len()`.
+    fn get(&self, i: usize) -> Self::Item;
+
+    /// Return whether there are no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a, T> Indexable for &'a [T] {
+    type Item = &'a T;
+
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn get(&self, i: usize) -> &'a T {
+        &self[i]
+    }
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, as by
+/// [`bisect_left`].
+///
+/// Only `usize` index arithmetic is used, with midpoints taken so they can't overflow, so this
+/// is safe for any `len()` up to `usize::MAX`.
+pub fn bisect_left_indexable<S>(a: &S, x: &S::Item) -> usize
+where
+    S: Indexable,
+    S::Item: PartialOrd,
+{
+    search(0, a.len(), |mid| a.get(mid) < *x)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, as by
+/// [`bisect_right`].
+///
+/// The same overflow guarantees hold as for [`bisect_left_indexable`].
+pub fn bisect_right_indexable<S>(a: &S, x: &S::Item) -> usize
+where
+    S: Indexable,
+    S::Item: PartialOrd,
+{
+    search(0, a.len(), |mid| {
+        x.partial_cmp(&a.get(mid)) != Some(Ordering::Less)
+    })
+}
+
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, and return the index it was
+/// inserted at, as long as `a` has fewer than `max_len` elements.
+///
+/// If `a` contains `x`, insert it just *after* the *rightmost* occurence of `x`. If `a` is
+/// already at or over `max_len`, `x` is handed back as `Err(x)` and `a` isn't modified, so
+/// callers can apply backpressure rather than evicting existing elements.
+pub fn try_insort_right<T>(a: &mut Vec<T>, x: T, max_len: usize) -> Result<usize, T>
+where
+    T: Ord,
+{
+    if a.len() >= max_len {
+        return Err(x);
+    }
+    let i = bisect_right(a, &x);
+    a.insert(i, x);
+    Ok(i)
+}
+
+/// Merge `a` and `b` into a new sorted vector, assuming both are sorted.
+///
+/// The merge is stable: elements of `a` come before equal elements of `b`.
+pub fn merge_sorted<T>(a: &[T], b: &[T]) -> Vec<T>
+where
+    T: Ord + Clone,
+{
+    merge_iter(a, b).cloned().collect()
+}
+
+/// Return an iterator over the elements of `a` and `b` in sorted order, assuming both are
+/// sorted, without allocating.
+///
+/// This is the lazy counterpart to [`merge_sorted`], yielding references into the original
+/// slices, with elements of `a` before equal elements of `b`.
+pub fn merge_iter<'a, T>(a: &'a [T], b: &'a [T]) -> impl Iterator<Item = &'a T> + 'a
+where
+    T: Ord,
+{
+    let (mut i, mut j) = (0, 0);
+    std::iter::from_fn(move || {
+        let next = match (a.get(i), b.get(j)) {
+            (Some(x), Some(y)) if y < x => {
+                j += 1;
+                y
+            }
+            (Some(x), _) => {
+                i += 1;
+                x
+            }
+            (None, Some(y)) => {
+                j += 1;
+                y
+            }
+            (None, None) => return None,
+        };
+        Some(next)
+    })
+}
+
+/// Return the smallest integer in `lo..=hi` for which `feasible` is `true`, or `None` if there
+/// is none, assuming `feasible` is `false` up to some point and `true` from then on.
+///
+/// This is bisection over an implicit range of candidate answers rather than a slice, e.g. to
+/// find the smallest capacity that meets a requirement. `feasible` is called
+/// `O(log(hi - lo))` times, and the full `i64` range can be searched without overflow.
+pub fn bisect_answer<F>(lo: i64, hi: i64, mut feasible: F) -> Option<i64>
+where
+    F: FnMut(i64) -> bool,
+{
+    let end = i128::from(hi) + 1;
+    let (mut lo, mut hi) = (i128::from(lo), end);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if feasible(mid as i64) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    if lo >= end {
+        None
+    } else {
+        Some(lo as i64)
+    }
+}
+
+/// A hook called on every probe of [`bisect_left_observed`], e.g. to record searches to a
+/// logging or metrics system.
+pub trait BisectObserver {
+    /// Called after comparing the element at `mid` against the target, while searching the
+    /// bracket `lo..hi`.
+    ///
+    /// `ord` is how the element compares to the target, or `None` if they're incomparable.
+    fn on_probe(&mut self, lo: usize, mid: usize, hi: usize, ord: Option<Ordering>);
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, reporting each
+/// probe to `observer`.
+///
+/// The index and the sequence of probes are the same as [`bisect_left`]'s. Unlike
+/// `bisect_left_trace` (behind the `trace` feature), this doesn't need `T: Debug` or build a
+/// string, so it's suitable for leaving enabled in production.
+pub fn bisect_left_observed<T, O>(a: &[T], x: &T, observer: &mut O) -> usize
+where
+    T: PartialOrd,
+    O: BisectObserver + ?Sized,
+{
+    let (mut lo, mut hi) = (0, a.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let ord = a[mid].partial_cmp(x);
+        observer.on_probe(lo, mid, hi, ord);
+        if ord == Some(Ordering::Less) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Return the fraction of elements of `a` less than or equal to `x`, assuming `a` is sorted.
+///
+/// This is `bisect_right(a, x) / a.len()`, the empirical cumulative distribution of `a` at `x`,
+/// or equivalently the percentile rank of `x` scaled to `0.0..=1.0`. An empty `a` gives `0.0`.
+pub fn bisect_normalized<T>(a: &[T], x: &T) -> f64
+where
+    T: PartialOrd,
+{
+    if a.is_empty() {
+        return 0.0;
+    }
+    bisect_right(a, x) as f64 / a.len() as f64
+}
+
+/// Return the index where `x` should be inserted in `a`, ignoring elements marked as deleted,
+/// assuming the live elements of `a` are sorted.
+///
+/// Elements with `deleted[i] == true` are tombstones: their values are never compared, so they
+/// may be stale. The result is one past the last live element less than `x`, or `0` if there is
+/// none, so all live elements before it are `< x` and all live elements from it on are `>= x`.
+///
+/// A probe that lands on a tombstone scans forward to the next live element, so this takes
+/// `O(log n)` comparisons but `O(d log n)` time, where `d` is the longest run of tombstones.
+///
+/// # Panics
+///
+/// Panics if `deleted` and `a` have different lengths.
+pub fn bisect_left_live<T>(a: &[T], deleted: &[bool], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    assert_eq!(
+        a.len(),
+        deleted.len(),
+        "one deleted flag is needed per element"
+    );
+    search(0, a.len(), |mid| {
+        match deleted[mid..].iter().position(|&d| !d) {
+            Some(offset) => a[mid + offset] < *x,
+            None => false,
+        }
+    })
+}
+
+/// Return the indices of the two keys of a lookup table bracketing `x`, and the weight for
+/// interpolating between them, assuming `table_keys` is sorted.
+///
+/// The result `(i, j, t)` has `table_keys[i] <= x < table_keys[j]` with `j == i + 1`, and
+/// `t = (x - table_keys[i]) / (table_keys[j] - table_keys[i])` in `0.0..1.0`, so the
+/// interpolated value is `values[i] * (1.0 - t) + values[j] * t`. A key equal to `x` is `i`,
+/// with `t == 0.0`. Outside the table the lookup is clamped to `(0, 0, 0.0)` below the first
+/// key and to `(last, last, 0.0)` from the last key on. A NaN `x` is `(0, 0, NaN)`, so the
+/// interpolated value is NaN too.
+///
+/// # Panics
+///
+/// Panics if `table_keys` is empty.
+pub fn lut_lookup(table_keys: &[f64], x: f64) -> (usize, usize, f64) {
+    assert!(!table_keys.is_empty(), "lookup table has no keys");
+    let last = table_keys.len() - 1;
+    if x.is_nan() {
+        return (0, 0, f64::NAN);
+    }
+    if x < table_keys[0] {
+        return (0, 0, 0.0);
+    }
+    if x >= table_keys[last] {
+        return (last, last, 0.0);
+    }
+    let i = bisect_right(table_keys, &x) - 1;
+    let t = (x - table_keys[i]) / (table_keys[i + 1] - table_keys[i]);
+    (i, i + 1, t)
+}
+
+/// A violation of the unique-sorted invariant found by [`assert_unique_sorted`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UniqueSortedError {
+    /// The elements at these adjacent indices are equal.
+    Duplicate(usize, usize),
+    /// The elements at these adjacent indices are out of order.
+    Unsorted(usize, usize),
+}
+
+impl fmt::Display for UniqueSortedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UniqueSortedError::Duplicate(i, j) => {
+                write!(f, "elements at {} and {} are equal", i, j)
+            }
+            UniqueSortedError::Unsorted(i, j) => {
+                write!(f, "elements at {} and {} are out of order", i, j)
+            }
+        }
+    }
+}
+
+impl Error for UniqueSortedError {}
+
+/// Check that `a` is sorted with no duplicates, returning the first adjacent pair of indices
+/// that breaks this.
+///
+/// A vector maintained only with unique insertions should always pass. The check walks all of
+/// `a` once, in `O(n)` comparisons.
+pub fn assert_unique_sorted<T>(a: &[T]) -> Result<(), UniqueSortedError>
+where
+    T: Ord,
+{
+    for (i, pair) in a.windows(2).enumerate() {
+        match pair[0].cmp(&pair[1]) {
+            Ordering::Less => {}
+            Ordering::Equal => return Err(UniqueSortedError::Duplicate(i, i + 1)),
+            Ordering::Greater => return Err(UniqueSortedError::Unsorted(i, i + 1)),
+        }
+    }
+    Ok(())
+}
+
+/// Insert every element of `xs` in `a`, keeping it sorted assuming `a` is sorted.
+///
+/// Elements equal to ones already in `a` go after them, as with [`insort_right`], and equal
+/// elements of `xs` keep their relative order. Capacity for all of `xs` is reserved up front,
+/// so `a` is reallocated at most once. The elements are appended and the two sorted runs merged
+/// by the standard library's stable sort, which takes `O(n + m log m)` comparisons rather than
+/// the `O(m log n)` comparisons and `O(n m)` moves of inserting one at a time.
+pub fn insort_all_right<T>(a: &mut Vec<T>, xs: Vec<T>)
+where
+    T: Ord,
+{
+    insort_all_right_with_capacity(a, xs, 0);
+}
+
+/// Insert every element of `xs` in `a` as by [`insort_all_right`], reserving room for at least
+/// `additional` more elements.
+///
+/// Callers making several merges can reserve for all of them with the first, so the later ones
+/// don't reallocate. The reservation is `max(additional, xs.len())`, so `a` is never
+/// reallocated more than once by a single call.
+pub fn insort_all_right_with_capacity<T>(a: &mut Vec<T>, xs: Vec<T>, additional: usize)
+where
+    T: Ord,
+{
+    a.reserve(std::cmp::max(additional, xs.len()));
+    a.extend(xs);
+    a.sort();
+}
+
+/// Return an iterator over the elements of `a` in the half-open interval `[low, high)` along
+/// with their indices in `a`, assuming `a` is sorted.
+///
+/// The indices are contiguous, starting at `bisect_left(a, low)`. The iterator is empty if the
+/// interval is empty or inverted.
+pub fn range_entries<'a, T>(a: &'a [T], low: &T, high: &T) -> impl Iterator<Item = (usize, &'a T)>
+where
+    T: PartialOrd,
+{
+    let offset = bisect_left(a, low);
+    between(a, low, high)
+        .iter()
+        .enumerate()
+        .map(move |(i, e)| (offset + i, e))
+}
+
+/// Return the index where `target` should be inserted in `a`, assuming `a` is sorted either
+/// ascending or descending under `cmp`, before any elements equal to it.
+///
+/// The direction is decided by comparing the first and last elements: if the first is greater,
+/// `a` is searched as descending, otherwise as ascending. All-equal slices are sorted both ways
+/// and give the same result either way.
+pub fn bisect_auto_by<T, F>(a: &[T], target: &T, cmp: F) -> usize
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    let last = match a.len().checked_sub(1) {
+        Some(last) => last,
+        None => return 0,
+    };
+    let ahead = if cmp(&a[0], &a[last]) == Ordering::Greater {
+        Ordering::Greater
+    } else {
+        Ordering::Less
+    };
+    search(0, a.len(), |mid| cmp(&a[mid], target) == ahead)
+}
+
+/// Return the part of [`equal_range`] of `x` in `a` that lies within `window`, assuming `a` is
+/// sorted.
+///
+/// If the run of elements equal to `x` doesn't overlap `window`, the result is an empty range
+/// at whichever end of `window` the run is clamped to.
+pub fn equal_range_clamped<T>(a: &[T], x: &T, window: Range<usize>) -> Range<usize>
+where
+    T: PartialOrd,
+{
+    let range = equal_range(a, x);
+    let start = std::cmp::min(std::cmp::max(range.start, window.start), window.end);
+    let end = std::cmp::max(std::cmp::min(range.end, window.end), start);
+    start..end
+}
+
+/// Return the index where the target built by `make_target` should be inserted in `a`, assuming
+/// `a` is sorted, as by [`bisect_left`].
+///
+/// An empty `a` returns `0` without calling `make_target`, which saves building an expensive
+/// target when searching many empty buckets. Otherwise it's called exactly once.
+pub fn bisect_left_lazy<T, F>(a: &[T], make_target: F) -> usize
+where
+    T: PartialOrd,
+    F: FnOnce() -> T,
+{
+    if a.is_empty() {
+        return 0;
+    }
+    bisect_left(a, &make_target())
+}
+
+/// Return the index `a[i]` should be moved to, assuming `a` is sorted except for the element at
+/// `i`, e.g. after it was modified in place.
+///
+/// The element moves as little as possible: past elements greater than it if it's too small, or
+/// past elements less than it if it's too large, but never past equal elements. Moving it is
+/// then a rotation, `a[j..=i].rotate_right(1)` if the result `j` is before `i`, or
+/// `a[i..=j].rotate_left(1)` if after.
+///
+/// # Panics
+///
+/// Panics if `i` is out of bounds of `a`.
+pub fn resort_position<T>(a: &[T], i: usize) -> usize
+where
+    T: Ord,
+{
+    let x = &a[i];
+    let left = bisect_right(&a[..i], x);
+    if left < i {
+        left
+    } else {
+        i + bisect_left(&a[i + 1..], x)
+    }
+}
+
+/// Move `a[i]` to its sorted position, assuming `a` is sorted except for the element at `i`,
+/// and return its new index.
+///
+/// The position is found by [`resort_position`] and the element moved with a single rotation,
+/// so this takes `O(log n)` comparisons and `O(n)` moves rather than re-sorting all of `a`. If
+/// `a[i]` is already in place, nothing is moved.
+///
+/// # Panics
+///
+/// Panics if `i` is out of bounds of `a`.
+pub fn resort_move<T>(a: &mut [T], i: usize) -> usize
+where
+    T: Ord,
+{
+    let j = resort_position(a, i);
+    match j.cmp(&i) {
+        Ordering::Less => a[j..=i].rotate_right(1),
+        Ordering::Greater => a[i..=j].rotate_left(1),
+        Ordering::Equal => {}
+    }
+    j
+}
+
+/// Comparison of a value against a value of another type, so a slice can be searched by a key
+/// without building a whole element to compare against.
+///
+/// Every `T: Ord` can be compared against itself.
+pub trait Comparison<Rhs: ?Sized> {
+    /// Return how `self` compares to `other`.
+    fn compare(&self, other: &Rhs) -> Ordering;
+}
+
+impl<T> Comparison<T> for T
+where
+    T: Ord,
+{
+    fn compare(&self, other: &T) -> Ordering {
+        self.cmp(other)
+    }
+}
+
+/// Return the index where `key` should be inserted in `a`, assuming `a` is sorted consistently
+/// with comparing its elements to `key`, before any elements equal to it.
+///
+/// This is [`bisect_left_by`] with the comparison supplied by the [`Comparison`] trait rather
+/// than a closure.
+pub fn bisect_left_het<T, K>(a: &[T], key: &K) -> usize
+where
+    T: Comparison<K>,
+    K: ?Sized,
+{
+    search(0, a.len(), |mid| a[mid].compare(key) == Ordering::Less)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, along with how
+/// the element at that index compares to `x`, or `None` if the index is `a.len()` or the two
+/// can't be compared.
+///
+/// The index is the same as [`bisect_left`]'s. If `a` really is sorted and every element can be
+/// compared with `x`, the ordering is always `Equal` or `Greater`, so callers can check their
+/// assumption without indexing again. A `None` with an index below `a.len()` means the element
+/// there can't be compared with `x`, e.g. either is a NaN, which breaks that assumption too.
+pub fn bisect_left_checked_at<T>(a: &[T], x: &T) -> (usize, Option<Ordering>)
+where
+    T: PartialOrd,
+{
+    let i = bisect_left(a, x);
+    (i, a.get(i).and_then(|e| e.partial_cmp(x)))
+}
+
+/// Return the index of the first interval in `intervals` overlapping `query`, or `None` if there
+/// is none.
+///
+/// Intervals are half-open `(start, end)` pairs, sorted by `start` but possibly overlapping each
+/// other. Only intervals starting before `query.1` can overlap it, so the search bisects to the
+/// end of those and then scans them for the first ending after `query.0`. Since ends aren't
+/// sorted, this scan can take `O(k)` for the `k` candidates; use [`stab`] if the intervals are
+/// known to be disjoint.
+pub fn first_overlapping<T>(intervals: &[(T, T)], query: &(T, T)) -> Option<usize>
+where
+    T: Ord,
+{
+    let candidates = search(0, intervals.len(), |mid| intervals[mid].0 < query.1);
+    intervals[..candidates]
+        .iter()
+        .position(|interval| interval.1 > query.0)
+}
+
+/// How [`rank_ratio`] ranks a value tied with elements of the slice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankMethod {
+    /// Count only the elements less than the value, as [`bisect_left`] does.
+    Lower,
+    /// Count the elements less than or equal to the value, as [`bisect_right`] does.
+    Upper,
+    /// Average the `Lower` and `Upper` counts.
+    Average,
+}
+
+/// Return the rank of `x` among the elements of `a` as a fraction of `a.len()`, assuming `a` is
+/// sorted, with ties handled according to `method`.
+///
+/// The result is in `0.0..=1.0`, and is the same for every method if `a` doesn't contain `x`.
+/// An empty `a` gives `0.0`.
+pub fn rank_ratio<T>(a: &[T], x: &T, method: RankMethod) -> f64
+where
+    T: PartialOrd,
+{
+    if a.is_empty() {
+        return 0.0;
+    }
+    let rank = match method {
+        RankMethod::Lower => bisect_left(a, x) as f64,
+        RankMethod::Upper => bisect_right(a, x) as f64,
+        RankMethod::Average => {
+            let range = equal_range(a, x);
+            (range.start + range.end) as f64 / 2.0
+        }
+    };
+    rank / a.len() as f64
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, before any
+/// equal strings.
+///
+/// Strings are compared by `str::cmp`, i.e. lexicographically by bytes, so a prefix sorts
+/// before the strings it's a prefix of. Nothing is allocated.
+pub fn bisect_left_str(a: &[&str], x: &str) -> usize {
+    search(0, a.len(), |mid| a[mid] < x)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, after any
+/// equal strings.
+///
+/// Strings are compared as by [`bisect_left_str`].
+pub fn bisect_right_str(a: &[&str], x: &str) -> usize {
+    search(0, a.len(), |mid| a[mid] <= x)
+}
+
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, before any equal strings.
+///
+/// Strings are compared by `str::cmp`, as by [`bisect_left_str`].
+pub fn insort_left_str(a: &mut Vec<String>, x: String) {
+    let i = search(0, a.len(), |mid| a[mid] < x);
+    a.insert(i, x);
+}
+
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, after any equal strings.
+///
+/// Strings are compared by `str::cmp`, as by [`bisect_left_str`].
+pub fn insort_right_str(a: &mut Vec<String>, x: String) {
+    let i = search(0, a.len(), |mid| a[mid] <= x);
+    a.insert(i, x);
+}
+
+/// Return the index where a value should be inserted in `a`, assuming `a` is sorted except for
+/// elements displaced by at most `local_window` places, according to a comparator function.
+///
+/// `primary` follows the same convention as [`bisect_left_by`]. After bisecting, the
+/// `local_window` elements on each side of the result are scanned, and the result is refined to
+/// the number of elements `primary` reports as `Less`, assuming every element before the window
+/// is `Less` and none after it are. That's where the value would go if `a` were fully sorted.
+///
+/// This is best-effort: disorder wider than `local_window` can break the assumption about the
+/// elements outside the window, and then the result is only approximate.
+pub fn bisect_left_tolerant<T, F>(a: &[T], primary: F, local_window: usize) -> usize
+where
+    F: Fn(&T) -> Ordering,
+{
+    let i = search(0, a.len(), |mid| primary(&a[mid]) == Ordering::Less);
+    let lo = i.saturating_sub(local_window);
+    let hi = std::cmp::min(i.saturating_add(local_window), a.len());
+    lo + a[lo..hi]
+        .iter()
+        .filter(|e| primary(e) == Ordering::Less)
+        .count()
+}
+
+/// Return the index of the first gap in `a` along with the value missing there, or `None` if
+/// `a` is contiguous, assuming `a` is strictly increasing.
+///
+/// `a` is contiguous up to index `i` if `a[i] == a[0] + i`. Since the values are strictly
+/// increasing, once that fails it fails for every later index, so the first gap is found by
+/// bisection in `O(log n)` comparisons, e.g. to find the first missing sequence number in a log.
+pub fn first_gap(a: &[i64]) -> Option<(usize, i64)> {
+    let first = i128::from(*a.first()?);
+    let i = search(0, a.len(), |mid| i128::from(a[mid]) - first == mid as i128);
+    if i == a.len() {
+        None
+    } else {
+        Some((i, a[0] + i as i64))
+    }
+}
+
+/// Return the number of distinct values in `a` less than `x`, assuming `a` is sorted.
+///
+/// Unlike [`count_lt`], each run of equal elements counts once. The runs are skipped with
+/// [`distinct_iter`], so this takes `O(d log n)` comparisons for `d` distinct values below `x`.
+pub fn distinct_count_lt<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    distinct_iter(&a[..bisect_left(a, x)]).count()
+}
+
+/// Whether [`bisect_f64`] includes elements equal to the target on its left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    /// Elements equal to the target come before the index, as with [`bisect_right`].
+    Inclusive,
+    /// Elements equal to the target come after the index, as with [`bisect_left`].
+    Exclusive,
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, with
+/// `endpoint` deciding which side elements equal to `x` fall on.
+///
+/// With [`Endpoint::Inclusive`], `a[..i]` is every element `<= x`, and with
+/// [`Endpoint::Exclusive`] every element `< x`, so e.g. the elements in `(lo, hi]` are
+/// `a[bisect_f64(a, lo, Inclusive)..bisect_f64(a, hi, Inclusive)]`. Comparisons follow IEEE 754,
+/// so `-0.0` and `+0.0` are equal and both count as equal to a target of either sign. Use
+/// [`OrdF64`] to tell them apart.
+pub fn bisect_f64(a: &[f64], x: f64, endpoint: Endpoint) -> usize {
+    match endpoint {
+        Endpoint::Inclusive => bisect_right(a, &x),
+        Endpoint::Exclusive => bisect_left(a, &x),
+    }
+}
+
+/// The result of a search through the C ABI wrappers such as [`bisect_left_i32_capi`].
+#[cfg(feature = "capi")]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchResultC {
+    /// The insertion index, as from [`bisect_left`].
+    pub index: usize,
+    /// `1` if the element at `index` is equal to the target, otherwise `0`.
+    pub found: u8,
+}
+
+/// Search `a` for `x` as by [`bisect_left`], returning the result in its C representation.
+///
+/// This is the safe Rust counterpart of the `_capi` wrappers.
+#[cfg(feature = "capi")]
+pub fn bisect_left_c<T>(a: &[T], x: &T) -> SearchResultC
+where
+    T: PartialOrd,
+{
+    let index = bisect_left(a, x);
+    SearchResultC {
+        index,
+        found: u8::from(a.get(index) == Some(x)),
+    }
+}
+
+#[cfg(feature = "capi")]
+macro_rules! bisect_left_capi {
+    ($($name:ident: $t:ty),* $(,)?) => {$(
+        #[doc = concat!(
+            "Search the `len` `", stringify!($t), "`s at `ptr` for `target` as by ",
+            "[`bisect_left`], for calling through the C ABI.\n\n",
+            "# Safety\n\n",
+            "Unless `len` is `0`, `ptr` must be non-null, aligned and point to `len` initialized, ",
+            "sorted values, which must not be modified during the call. If `len` is `0`, `ptr` ",
+            "may be null."
+        )]
+        #[no_mangle]
+        pub unsafe extern "C" fn $name(ptr: *const $t, len: usize, target: $t) -> SearchResultC {
+            if len == 0 {
+                return SearchResultC { index: 0, found: 0 };
+            }
+            // SAFETY: the caller guarantees `ptr` points to `len` initialized values
+            let a = unsafe { std::slice::from_raw_parts(ptr, len) };
+            bisect_left_c(a, &target)
+        }
+    )*};
+}
+
+#[cfg(feature = "capi")]
+bisect_left_capi!(
+    bisect_left_i32_capi: i32,
+    bisect_left_i64_capi: i64,
+    bisect_left_u32_capi: u32,
+    bisect_left_u64_capi: u64,
+    bisect_left_f64_capi: f64,
+);
+
+/// Return the index where `x` should be inserted in `a[within]`, assuming `a` is sorted,
+/// relative to the start of `within`.
+///
+/// [`bisect_left_slice`] returns an index into all of `a`; this returns one into `&a[within]`,
+/// i.e. the global index minus the start of `within`. An insertion at the start of the range
+/// gives `0`.
+///
+/// # Panics
+///
+/// Panics if `within` is out of bounds of `a`.
+pub fn bisect_left_slice_local<T, I>(a: &[T], x: &T, within: I) -> usize
+where
+    I: RangeBounds<usize>,
+    T: PartialOrd,
+{
+    let (lo, hi) = bounds_to_indices(a, within);
+    search(lo, hi, |mid| a[mid] < *x) - lo
+}
+
+/// Return the index of the first element of `a` not less than `x`, assuming `a` is sorted, or
+/// `a.len()` if there is none.
+///
+/// This is C++'s `std::lower_bound` under its usual name, and the same as [`bisect_left`]:
+///
+/// ```
+/// use bisection::{lower_bound, upper_bound};
+///
+/// // std::vector<int> v = {1, 2, 2, 3};
+/// // auto lo = std::lower_bound(v.begin(), v.end(), 2) - v.begin();
+/// // auto hi = std::upper_bound(v.begin(), v.end(), 2) - v.begin();
+/// let v = vec![1, 2, 2, 3];
+/// let lo = lower_bound(&v, &2);
+/// let hi = upper_bound(&v, &2);
+/// assert_eq!((lo, hi), (1, 3));
+/// ```
+pub fn lower_bound<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    bisect_left(a, x)
+}
+
+/// Return the index of the first element of `a` greater than `x`, assuming `a` is sorted, or
+/// `a.len()` if there is none.
+///
+/// This is C++'s `std::upper_bound`, and the same as [`bisect_right`].
+pub fn upper_bound<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    bisect_right(a, x)
+}
+
+/// Return the index of the first element of `a` for which `f` doesn't return `Less`, assuming
+/// `a` is sorted.
+///
+/// `f` returns how an element compares to the target, as with a C++ comparator, and follows
+/// the same convention as [`bisect_left_by`].
+pub fn lower_bound_by<T, F>(a: &[T], f: F) -> usize
+where
+    F: FnMut(&T) -> Ordering,
+{
+    lower_bound_slice_by(a, .., f)
+}
+
+/// Return the index of the first element of `a` for which `f` returns `Greater`, assuming `a`
+/// is sorted.
+///
+/// `f` follows the same convention as for [`lower_bound_by`], so unlike [`bisect_right_by`] the
+/// same closure can be passed to both.
+pub fn upper_bound_by<T, F>(a: &[T], f: F) -> usize
+where
+    F: FnMut(&T) -> Ordering,
+{
+    upper_bound_slice_by(a, .., f)
+}
+
+/// Return the index of the first element of `a[within]` not less than `x`, assuming `a` is
+/// sorted, as by [`bisect_left_slice`].
+///
+/// # Panics
+///
+/// Panics if `within` is out of bounds of `a`.
+pub fn lower_bound_slice<T, I>(a: &[T], x: &T, within: I) -> usize
+where
+    I: RangeBounds<usize>,
+    T: PartialOrd,
+{
+    bisect_left_slice(a, x, within)
+}
+
+/// Return the index of the first element of `a[within]` greater than `x`, assuming `a` is
+/// sorted, as by [`bisect_right_slice`].
+///
+/// # Panics
+///
+/// Panics if `within` is out of bounds of `a`.
+pub fn upper_bound_slice<T, I>(a: &[T], x: &T, within: I) -> usize
+where
+    I: RangeBounds<usize>,
+    T: PartialOrd,
+{
+    bisect_right_slice(a, x, within)
+}
+
+/// Return the index of the first element of `a[within]` for which `f` doesn't return `Less`,
+/// assuming `a` is sorted.
+///
+/// `f` follows the same convention as for [`lower_bound_by`].
+///
+/// # Panics
+///
+/// Panics if `within` is out of bounds of `a`.
+pub fn lower_bound_slice_by<T, I, F>(a: &[T], within: I, f: F) -> usize
+where
+    I: RangeBounds<usize>,
+    F: FnMut(&T) -> Ordering,
+{
+    bisect_left_slice_by(a, within, f)
+}
+
+/// Return the index of the first element of `a[within]` for which `f` returns `Greater`,
+/// assuming `a` is sorted.
+///
+/// `f` follows the same convention as for [`lower_bound_by`].
+///
+/// # Panics
+///
+/// Panics if `within` is out of bounds of `a`.
+pub fn upper_bound_slice_by<T, I, F>(a: &[T], within: I, mut f: F) -> usize
+where
+    I: RangeBounds<usize>,
+    F: FnMut(&T) -> Ordering,
+{
+    bisect_right_slice_by(a, within, |e| f(e).reverse())
+}
+
+/// Check that `a` is sorted in non-decreasing order, as every search in this crate assumes,
+/// returning the first index `i` where `a[i - 1] <= a[i]` doesn't hold if it isn't.
+///
+/// Unlike a debug assertion, this can be called at runtime on untrusted input before searching
+/// it. It takes `O(n)` comparisons. Adjacent elements that can't be compared, e.g. a NaN next to
+/// anything, count as out of order.
+pub fn validate_sorted<T>(a: &[T]) -> Result<(), usize>
+where
+    T: PartialOrd,
+{
+    match a.windows(2).position(|pair| {
+        !matches!(
+            pair[0].partial_cmp(&pair[1]),
+            Some(Ordering::Less | Ordering::Equal)
+        )
+    }) {
+        Some(i) => Err(i + 1),
+        None => Ok(()),
+    }
+}
+
+/// Check that `a` is sorted according to a comparator function, returning the first index `i`
+/// where `f(&a[i - 1], &a[i])` returns `Greater` if it isn't.
+pub fn validate_sorted_by<T, F>(a: &[T], mut f: F) -> Result<(), usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    match a
+        .windows(2)
+        .position(|pair| f(&pair[0], &pair[1]) == Ordering::Greater)
+    {
+        Some(i) => Err(i + 1),
+        None => Ok(()),
+    }
+}
+
+/// Return the index where `x` should be inserted in `a`, skipping a leading block of empty
+/// slots marked with `sentinel`, assuming the rest of `a` is sorted.
+///
+/// The sentinel slots must all come first, e.g. `[MIN, MIN, 3, 5, 8]` for a sentinel of
+/// `i32::MIN`, with no sentinels among the valid values after them. The end of the block is
+/// found by bisecting for the first slot that isn't `sentinel`, and only the valid values after
+/// it are searched, so the result is never inside the block, whatever the sentinel's value.
+pub fn bisect_left_skip_sentinel(a: &[i32], x: i32, sentinel: i32) -> usize {
+    let start = search(0, a.len(), |mid| a[mid] == sentinel);
+    bisect_left_slice(a, &x, start..)
+}
+
+/// Return the range of indices of `a` in each bin delimited by `edges`, assuming `a` is sorted.
+///
+/// Bin `i` is the half-open interval `[edges[i], edges[i + 1])`, so `n + 1` edges give `n`
+/// ranges. Each edge is bisected once, and consecutive ranges share their boundary, so together
+/// they cover exactly the elements from the first edge up to but excluding the last.
+///
+/// # Panics
+///
+/// Panics if `edges` isn't sorted.
+pub fn multi_bin_ranges<T>(a: &[T], edges: &[T]) -> Vec<Range<usize>>
+where
+    T: PartialOrd,
+{
+    assert!(
+        edges.windows(2).all(|w| w[0] <= w[1]),
+        "bin edges must be sorted"
+    );
+    let bounds: Vec<usize> = edges.iter().map(|e| bisect_left(a, e)).collect();
+    bounds.windows(2).map(|w| w[0]..w[1]).collect()
+}
+
+/// Return the index of an element of `a` for which `ord` returns `Equal` and `eq` returns
+/// `true`, assuming `a` is sorted according to `ord`, or `None` if there is none.
+///
+/// `ord` follows the same convention as [`bisect_left_by`]. The run of elements it considers
+/// equal is found by bisection and then scanned for the first satisfying `eq`, which allows an
+/// equality stricter than the ordering, e.g. a case-sensitive match in a case-insensitively
+/// sorted list. The scan is linear in the length of the run.
+pub fn bisect_find_eq<T, O, E>(a: &[T], ord: O, eq: E) -> Option<usize>
+where
+    O: Fn(&T) -> Ordering,
+    E: Fn(&T) -> bool,
+{
+    let run = equal_range_by(a, ord);
+    a[run.clone()].iter().position(eq).map(|i| run.start + i)
+}
+
+/// A forward-only cached position for a sequence of searches with non-decreasing targets.
+///
+/// Each search gallops forward from the previous result, so a search `k` elements further on
+/// takes `O(log k)` comparisons, and evenly spaced targets cost amortized `O(1)` each. Unlike
+/// [`bisect_right_sorted_targets`], the targets don't need to be known up front, so searches can
+/// be interleaved with other work. Use [`Cursor`] if targets can also move backwards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MonotoneCursor {
+    index: usize,
+}
+
+impl MonotoneCursor {
+    /// Create a cursor at the start of the slice.
+    pub fn new() -> Self {
+        MonotoneCursor { index: 0 }
+    }
+
+    /// Return the result of the last search, or `0` before the first.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, as by
+    /// [`bisect_left`].
+    ///
+    /// `x` must not be less than the previous target, and `a` must be the same slice as for the
+    /// previous searches; this is checked in debug builds as far as possible.
+    pub fn search<T>(&mut self, a: &[T], x: &T) -> usize
+    where
+        T: PartialOrd,
+    {
+        debug_assert!(self.index <= a.len(), "cursor is past the end of the slice");
+        debug_assert!(
+            self.index == 0 || a[self.index - 1] < *x,
+            "targets must be non-decreasing"
+        );
+        self.index = gallop(self.index, a.len(), |mid| a[mid] < *x);
+        self.index
+    }
+}
+
+/// Return the index of the bin `u` falls in, given the cumulative weights of the bins, for
+/// sampling by inverse CDF.
+///
+/// `cumulative` must be non-decreasing and end at the total weight, so bin `i` covers
+/// `[cumulative[i - 1], cumulative[i])`, taking `cumulative[-1]` as `0.0`. Drawing `u`
+/// uniformly from `[0, total)` then selects each bin with probability proportional to its
+/// weight. A `u` exactly on a boundary `cumulative[i]` falls in the *next* bin, `i + 1`, so bins
+/// of zero weight are never selected. The result is [`bisect_right`], clamped into
+/// `0..cumulative.len()` so that a `u` of `total` or more selects the last bin.
+///
+/// # Panics
+///
+/// Panics if `cumulative` is empty.
+pub fn sample_index(cumulative: &[f64], u: f64) -> usize {
+    assert!(!cumulative.is_empty(), "no bins to sample from");
+    std::cmp::min(bisect_right(cumulative, &u), cumulative.len() - 1)
+}
+
+/// Return the indices of the largest element of `a` not greater than `x` and of the smallest
+/// element not less than `x`, assuming `a` is sorted.
+///
+/// Both come from the one descent of [`bounds_of`]. If `a` contains `x`, the floor is the *last*
+/// element equal to `x` and the ceiling the *first*, so the floor index is then at or after the
+/// ceiling index. Either is `None` if `x` is beyond that end of `a`.
+pub fn floor_ceil<T>(a: &[T], x: &T) -> (Option<usize>, Option<usize>)
+where
+    T: PartialOrd,
+{
+    let (left, right) = bounds_of(a, x);
+    let floor = right.checked_sub(1);
+    let ceil = if left < a.len() { Some(left) } else { None };
+    (floor, ceil)
+}
+
+/// Return the index where `x` should be inserted in the shared slice `a`, assuming `a` is
+/// sorted, as by [`bisect_left`].
+///
+/// The slice behind an `Arc` is immutable, so any number of threads can search clones of the
+/// same `Arc` concurrently without locking.
+pub fn bisect_left_shared<T>(a: &Arc<[T]>, x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    bisect_left(a, x)
+}
+
+/// Return the index where `x` should be inserted in the shared slice `a`, assuming `a` is
+/// sorted, as by [`bisect_right`].
+///
+/// See [`bisect_left_shared`] for concurrent use.
+pub fn bisect_right_shared<T>(a: &Arc<[T]>, x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    bisect_right(a, x)
+}
+
+/// Return the index where a value should be inserted in the shared slice `a`, assuming `a` is
+/// sorted, as by [`bisect_left_by`].
+///
+/// See [`bisect_left_shared`] for concurrent use.
+pub fn bisect_left_shared_by<T, F>(a: &Arc<[T]>, f: F) -> usize
+where
+    F: FnMut(&T) -> Ordering,
+{
+    bisect_left_by(a, f)
+}
+
+/// Return the index where a value should be inserted in the shared slice `a`, assuming `a` is
+/// sorted, as by [`bisect_right_by`], whose comparator convention it follows.
+///
+/// See [`bisect_left_shared`] for concurrent use.
+pub fn bisect_right_shared_by<T, F>(a: &Arc<[T]>, f: F) -> usize
+where
+    F: FnMut(&T) -> Ordering,
+{
+    bisect_right_by(a, f)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, using a
+/// precomputed table of partition boundaries to narrow the search.
+///
+/// `buckets[k]` is the start index of the `k`-th partition of `a`. The search first bisects the
+/// partitions by their first elements, read from `a` at the indices in `buckets`, and then
+/// bisects only within the one partition that can contain the result. The result is the same
+/// as [`bisect_left`].
+///
+/// # Panics
+///
+/// Panics in debug builds if `buckets` isn't non-decreasing or has an index past the end of `a`.
+/// Checking that takes `O(buckets.len())`, so release builds skip it, and the result for such a
+/// `buckets` is unspecified and may be a panic.
+pub fn bisect_left_bucketed<T>(a: &[T], buckets: &[usize], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    debug_assert!(
+        buckets.windows(2).all(|w| w[0] <= w[1]),
+        "bucket boundaries must be non-decreasing"
+    );
+    debug_assert!(
+        buckets.iter().all(|&b| b <= a.len()),
+        "bucket boundary out of bounds"
+    );
+    let k = search(0, buckets.len(), |mid| {
+        buckets[mid] < a.len() && a[buckets[mid]] < *x
+    });
+    let lo = if k == 0 { 0 } else { buckets[k - 1] };
+    let hi = buckets.get(k).cloned().unwrap_or(a.len());
+    bisect_left_slice(a, x, lo..hi)
+}
+
+/// Return the index of the first element of `a` equal to `x` as an `isize`, assuming `a` is
+/// sorted, or `-1` if `a` doesn't contain `x`.
+///
+/// This suits interop with APIs using the signed `-1` convention for "not found". The
+/// conversion can't overflow, since a slice never has more than `isize::MAX` elements.
+pub fn found_index_signed<T>(a: &[T], x: &T) -> isize
+where
+    T: PartialOrd,
+{
+    let i = bisect_left(a, x);
+    if a.get(i) == Some(x) {
+        i as isize
+    } else {
+        -1
+    }
+}
+
+/// Return the range of up to `radius` elements of `a` on each side of the insertion point of
+/// `x`, assuming `a` is sorted.
+///
+/// With `i = bisect_left(a, x)`, the range is `i - radius..i + radius` clamped to the bounds of
+/// `a`, so it holds `2 * radius` elements in the interior of `a` and fewer near its ends. Unlike
+/// [`around`], the window isn't shifted to make up for clamping, so it stays centered on `x`,
+/// as local fitting needs.
+pub fn neighborhood<T>(a: &[T], x: &T, radius: usize) -> Range<usize>
+where
+    T: PartialOrd,
+{
+    let i = bisect_left(a, x);
+    i.saturating_sub(radius)..std::cmp::min(i.saturating_add(radius), a.len())
+}
+
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, unless `a` already contains an
+/// element equal to it, in which case `x` is combined into that element with `combine`.
+///
+/// Returns the index of the inserted or combined element. This is the sorted vector analog of
+/// `entry(x).and_modify(..).or_insert(..)` on a map, e.g. for keeping counts: `a` only grows
+/// when `x` is new. If `a` has several elements equal to `x`, the leftmost is combined into.
+pub fn insort_combine<T, F>(a: &mut Vec<T>, x: T, combine: F) -> usize
+where
+    T: Ord,
+    F: Fn(&mut T, T),
+{
+    let i = bisect_left(a, &x);
+    if a.get(i) == Some(&x) {
+        combine(&mut a[i], x);
+    } else {
+        a.insert(i, x);
+    }
+    i
+}
+
+/// Bisect `a` for `x` in the coordinate space of `a.iter().rev()`, assuming `a` is sorted
+/// ascending.
+///
+/// Returns the position in the reversed (descending) view before which `x` would go, to the
+/// left of any elements equal to it in that view. It is the mirror of the forward right
+/// insertion point: `bisect_left_rev_view(a, x) + bisect_right(a, x) == a.len()`, so no
+/// reversed copy of `a` is needed.
+pub fn bisect_left_rev_view<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    a.len() - bisect_right(a, x)
+}
+
+/// Return the index where `key` should be inserted in `a`, assuming `a` is a slice of
+/// `(key, payload)` pairs sorted by key, comparing keys only.
+///
+/// This is the storage layout of a decorate-sort-undecorate (Schwartzian transform) sort; the
+/// payloads are never compared, so they need not be ordered at all. As with [`bisect_left`],
+/// `i` is to the left of any entries with key `key`.
+pub fn bisect_left_keyed_pair<K, V>(a: &[(K, V)], key: &K) -> usize
+where
+    K: PartialOrd,
+{
+    search(0, a.len(), |i| a[i].0 < *key)
+}
+
+/// Return the index where `key` should be inserted in `a`, assuming `a` is a slice of
+/// `(key, payload)` pairs sorted by key, comparing keys only.
+///
+/// Like [`bisect_left_keyed_pair`], but `i` is to the right of any entries with key `key`.
+pub fn bisect_right_keyed_pair<K, V>(a: &[(K, V)], key: &K) -> usize
+where
+    K: PartialOrd,
+{
+    search(0, a.len(), |i| a[i].0 <= *key)
+}
+
+/// Insert `(key, v)` in `a`, keeping it sorted by key assuming it is, after any entries with the
+/// same key, so that entries with equal keys stay in insertion order.
+pub fn insort_right_keyed_pair<K, V>(a: &mut Vec<(K, V)>, key: K, v: V)
+where
+    K: PartialOrd,
+{
+    let i = bisect_right_keyed_pair(a, &key);
+    a.insert(i, (key, v));
+}
+
+/// Insert `(key, v)` in `a`, keeping it sorted by key assuming it is, before any entries with the
+/// same key.
+pub fn insort_left_keyed_pair<K, V>(a: &mut Vec<(K, V)>, key: K, v: V)
+where
+    K: PartialOrd,
+{
+    let i = bisect_left_keyed_pair(a, &key);
+    a.insert(i, (key, v));
+}
+
+/// Return the range of entries of `a` with key `key`, assuming `a` is a slice of
+/// `(key, payload)` pairs sorted by key.
+///
+/// The range is empty, starting at the insertion point, if no entry has key `key`.
+pub fn equal_range_keyed_pair<K, V>(a: &[(K, V)], key: &K) -> Range<usize>
+where
+    K: PartialOrd,
+{
+    bisect_left_keyed_pair(a, key)..bisect_right_keyed_pair(a, key)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, along with
+/// whether `samples` spot-checked adjacent pairs of `a` were all in order.
+///
+/// The index is that of [`bisect_left`]. The pairs `(a[j], a[j + 1])` are picked
+/// pseudo-randomly, deterministically for a given length and `samples`, so this costs
+/// `O(log n + samples)` rather than the `O(n)` of a full [`validate_sorted`] scan. `true` is
+/// only a probabilistic signal, but `false` means `a` is definitely not sorted, so the
+/// precondition is violated and the index may be wrong.
+pub fn bisect_left_spotcheck<T>(a: &[T], x: &T, samples: usize) -> (usize, bool)
+where
+    T: PartialOrd,
+{
+    let i = bisect_left(a, x);
+    if a.len() < 2 {
+        return (i, true);
+    }
+    let pairs = (a.len() - 1) as u64;
+    // xorshift64, which must not be seeded with zero
+    let mut state = ((a.len() as u64) ^ ((samples as u64) << 32)) | 1;
+    let sorted = (0..samples).all(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let j = (state % pairs) as usize;
+        a[j + 1].partial_cmp(&a[j]) != Some(Ordering::Less)
+    });
+    (i, sorted)
+}
+
+/// Return the logical index where `x` should be inserted in the sorted sequence stored in the
+/// ring buffer `buf`, starting at physical index `head` and wrapping around.
+///
+/// The sequence is `buf[head], buf[(head + 1) % cap], ...` for `len` elements, where `cap` is
+/// `buf.len()`, and the result is in `0..=len` in that logical order, with the same
+/// convention as [`bisect_left`]. Logical index `i` is stored at `buf[(head + i) % cap]`.
+///
+/// # Panics
+///
+/// Panics if `len` is greater than `buf.len()`, or if `len` is nonzero and `head` is out of
+/// bounds of `buf`.
+pub fn bisect_left_ringbuf<T>(buf: &[T], head: usize, len: usize, x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    let cap = buf.len();
+    assert!(len <= cap, "length {} exceeds capacity {}", len, cap);
+    if len == 0 {
+        return 0;
+    }
+    assert!(
+        head < cap,
+        "head {} out of bounds of capacity {}",
+        head,
+        cap
+    );
+    search(0, len, |i| {
+        let j = if i < cap - head {
+            head + i
+        } else {
+            i - (cap - head)
+        };
+        buf[j] < *x
+    })
+}
+
+/// Evidence that a slice passed to [`safe_bisect_left`] is not sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortError {
+    /// The probed index at which the inconsistency was seen.
+    pub index: usize,
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "slice is not sorted around index {}", self.index)
+    }
+}
+
+impl Error for SortError {}
+
+/// Return the index where `x` should be inserted in `a`, like [`bisect_left`], or an error if
+/// the descent finds that `a` is not sorted.
+///
+/// Each probed midpoint is also checked against the elements already known to bracket it and
+/// against its right neighbour, at the cost of up to three extra comparisons per probe. This
+/// catches many unsorted inputs, in particular inversions on or next to the probe path, but
+/// not all of them: `Ok` is not proof that `a` is sorted. Use [`validate_sorted`] for a full
+/// `O(n)` check.
+pub fn safe_bisect_left<T>(a: &[T], x: &T) -> Result<usize, SortError>
+where
+    T: PartialOrd,
+{
+    let (mut lo, mut hi) = (0, a.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let e = &a[mid];
+        let inconsistent = (lo > 0 && *e < a[lo - 1])
+            || (hi < a.len() && a[hi] < *e)
+            || (mid + 1 < a.len() && a[mid + 1] < *e);
+        if inconsistent {
+            return Err(SortError { index: mid });
+        }
+        if *e < *x {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    Ok(lo)
+}
+
+/// Return the length in bytes of the longest prefix of `query` shared by any string in `a`,
+/// along with the range of strings of `a` that start with that prefix, assuming `a` is sorted.
+///
+/// Strings sharing a prefix are contiguous in sorted order, so each candidate prefix takes two
+/// bisections; candidates are tried from all of `query` down, on `char` boundaries, until one
+/// matches. If no string shares even the first `char` of `query`, the result is the empty
+/// prefix, shared by all of `a`: `(0, 0..a.len())`.
+pub fn lcp_range(a: &[&str], query: &str) -> (usize, Range<usize>) {
+    for len in (1..=query.len()).rev() {
+        if !query.is_char_boundary(len) {
+            continue;
+        }
+        let prefix = &query[..len];
+        let lo = search(0, a.len(), |i| a[i] < prefix);
+        let hi = search(lo, a.len(), |i| a[i].starts_with(prefix));
+        if lo < hi {
+            return (len, lo..hi);
+        }
+    }
+    (0, 0..a.len())
+}
+
+/// Return the index where `x` should be inserted in `a`, like [`bisect_left`], checking first
+/// whether `x` is outside the range of values in `a`.
+///
+/// A target at or below `a[0]` returns `0`, and one above the last element returns `a.len()`,
+/// each after a single comparison. The checks use the same comparisons as [`bisect_left`], so a
+/// target that isn't comparable with `a[0]`, e.g. a NaN, also returns `0`. Otherwise this costs two comparisons more than
+/// [`bisect_left`], so it pays off when many queries fall outside `a`, e.g. when filtering.
+pub fn bisect_left_guarded<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    let hi = a.len();
+    if hi == 0 || a[0].partial_cmp(x) != Some(Ordering::Less) {
+        return 0;
+    }
+    if a[hi - 1] < *x {
+        return hi;
+    }
+    search(1, hi - 1, |i| a[i] < *x)
+}
+
+/// Return the index where `x` should be inserted in `a`, like [`bisect_right`], checking first
+/// whether `x` is outside the range of values in `a`.
+///
+/// A target below `a[0]` returns `0`, and one at or above the last element returns `a.len()`,
+/// each after a single comparison; see [`bisect_left_guarded`]. As with [`bisect_right`], a
+/// target that isn't comparable with the last element, e.g. a NaN, returns `a.len()`.
+pub fn bisect_right_guarded<T>(a: &[T], x: &T) -> usize
+where
+    T: PartialOrd,
+{
+    let hi = a.len();
+    if hi == 0 || *x < a[0] {
+        return 0;
+    }
+    if x.partial_cmp(&a[hi - 1]) != Some(Ordering::Less) {
+        return hi;
+    }
+    search(1, hi - 1, |i| x.partial_cmp(&a[i]) != Some(Ordering::Less))
+}
+
+/// Return the number of elements of `a` below `x`, assuming `a` is sorted, along with their
+/// total, read from the prefix sums `cumulative` of `a`.
+///
+/// `cumulative[i]` must be the sum of `a[..i]`, so it starts at `0.0` and has one more element
+/// than `a`. The result is `(i, cumulative[i])` with `i` from [`bisect_left`].
+///
+/// # Panics
+///
+/// Panics if `cumulative.len()` is not `a.len() + 1`.
+pub fn rank_and_prefix_sum(a: &[f64], cumulative: &[f64], x: f64) -> (usize, f64) {
+    assert_eq!(
+        cumulative.len(),
+        a.len() + 1,
+        "prefix sums must have one more element than the slice"
+    );
+    let i = bisect_left(a, &x);
+    (i, cumulative[i])
+}
+
+/// Return the index where `target` should be inserted in `pages`, assuming it is a sorted slice
+/// of big-endian encoded `u64` keys.
+///
+/// Big-endian bytes compare like the numbers they encode, so `target` is encoded once and
+/// compared bytewise, with no decoding of the stored keys. The same convention as
+/// [`bisect_left`] applies.
+pub fn bisect_left_be_u64(pages: &[[u8; 8]], target: u64) -> usize {
+    bisect_left(pages, &target.to_be_bytes())
+}
+
+/// Return the index where `target` should be inserted in `pages`, assuming it is a sorted slice
+/// of big-endian encoded `u32` keys; see [`bisect_left_be_u64`].
+pub fn bisect_left_be_u32(pages: &[[u8; 4]], target: u32) -> usize {
+    bisect_left(pages, &target.to_be_bytes())
+}
+
+/// Return the index where `target` should be inserted in `pages`, assuming it is a sorted slice
+/// of `i64` keys encoded big-endian with the sign bit flipped.
+///
+/// Flipping the sign bit, i.e. encoding `(x as u64 ^ 1 << 63).to_be_bytes()`, makes negative
+/// keys sort bytewise before non-negative ones, so byte order matches numeric order across the
+/// sign boundary; see [`bisect_left_be_u64`].
+pub fn bisect_left_be_i64(pages: &[[u8; 8]], target: i64) -> usize {
+    bisect_left_be_u64(pages, target as u64 ^ 1 << 63)
+}
+
+/// Return the index where `x` would be inserted in `a` by [`insort_right`], along with a copy of
+/// the up to `k` elements around that point, without modifying `a`.
+///
+/// The context is `k / 2` elements before the insertion point and the remaining `k - k / 2`
+/// after it, so `x` would land between `context[..k / 2]` and the rest. Each side is clamped to
+/// the ends of `a` rather than shifted, so an insertion near an end has fewer context elements.
+pub fn insort_context_right<T>(a: &[T], x: &T, k: usize) -> (usize, Vec<T>)
+where
+    T: Ord + Clone,
+{
+    let i = bisect_right(a, x);
+    let start = i.saturating_sub(k / 2);
+    let end = std::cmp::min(i + (k - k / 2), a.len());
+    (i, a[start..end].to_vec())
+}
+
+/// How an element compares to the target of [`bisect_by_resolution`], with ties resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// The element is below the target.
+    Less,
+    /// The element equals the target, but the insertion point should be before it.
+    PreferLeft,
+    /// The element equals the target, but the insertion point should be after it.
+    PreferRight,
+    /// The element is above the target.
+    Greater,
+}
+
+/// Return the index where a value should be inserted in `a`, assuming it sorted, according to
+/// a comparator function that also decides where it goes among equal elements.
+///
+/// `f` returns how each element compares to the target, as for [`bisect_left_by`], but splits
+/// `Equal` in two: an element resolved as `PreferLeft` is searched past like `Greater`, as
+/// [`bisect_left_by`] does for every equal element, and one resolved as `PreferRight` like
+/// `Less`, as [`bisect_right_by`] does. The result `i` is such that all `e` in `a[..i]` are
+/// `Less` or `PreferRight` and all `e` in `a[i..]` are `PreferLeft` or `Greater`, so within a
+/// run of equal elements, the `PreferRight` ones must all come before the `PreferLeft` ones.
+pub fn bisect_by_resolution<T, F>(a: &[T], mut f: F) -> usize
+where
+    F: FnMut(&T) -> Resolution,
+{
+    search(0, a.len(), |i| match f(&a[i]) {
+        Resolution::Less | Resolution::PreferRight => true,
+        Resolution::PreferLeft | Resolution::Greater => false,
+    })
+}
+
+/// Return [`bisect_left`] of each of `queries` in `a`, assuming `a` is sorted, in the order of
+/// `queries`.
+///
+/// `queries` need not be sorted. They are visited in sorted order with a [`MonotoneCursor`], so
+/// a large batch costs `O(m log m)` for the sort plus about `O(m log(n / m))` comparisons for
+/// the searches, instead of `O(m log n)`. If some queries can't be compared with each other,
+/// each query is bisected independently instead. Queries that can't be compared with
+/// themselves, e.g. a NaN, are caught before sorting. Other incomparable pairs are only caught
+/// by checking the sorted order, so the standard library's sort may panic on them first, as it
+/// may for any comparator that isn't a total order.
+pub fn ranks_of<T>(a: &[T], queries: &[T]) -> Vec<usize>
+where
+    T: PartialOrd,
+{
+    let independently = || queries.iter().map(|q| bisect_left(a, q)).collect();
+    // Sorting needs a total order, so rule out incomparable queries before the sort sees them.
+    if queries.iter().any(|q| q.partial_cmp(q).is_none()) {
+        return independently();
+    }
+    let mut order: Vec<usize> = (0..queries.len()).collect();
+    order.sort_by(|&i, &j| {
+        queries[i]
+            .partial_cmp(&queries[j])
+            .unwrap_or(Ordering::Equal)
+    });
+    // The cursor needs each query to be at least the previous one
+    if order.windows(2).any(|w| {
+        !matches!(
+            queries[w[0]].partial_cmp(&queries[w[1]]),
+            Some(Ordering::Less | Ordering::Equal)
+        )
+    }) {
+        return independently();
+    }
+
+    let mut ranks = vec![0; queries.len()];
+    let mut cursor = MonotoneCursor::new();
+    for i in order {
+        ranks[i] = cursor.search(a, &queries[i]);
+    }
+    ranks
+}
+
+/// Return copies of the elements of `a` for which `primary` returns `Equal`, assuming `a` is
+/// sorted by it, sorted by `secondary` instead.
+///
+/// The matching run is found with [`equal_range_by`], so `primary` follows the convention of
+/// [`bisect_left_by`]. The sort by `secondary` is stable, so ties keep their order in `a`.
+pub fn equal_range_then_sort_by<T, F, G>(a: &[T], primary: F, secondary: G) -> Vec<T>
+where
+    T: Clone,
+    F: FnMut(&T) -> Ordering,
+    G: FnMut(&T, &T) -> Ordering,
+{
+    let mut matching = a[equal_range_by(a, primary)].to_vec();
+    matching.sort_by(secondary);
+    matching
+}
+
+/// A sorted slice tagged with a caller-supplied version, for optimistic concurrency.
+///
+/// The version is typically a generation counter bumped whenever the underlying data changes.
+/// Searches return it alongside their result, so a caller that acts on the result later can
+/// compare it against the current generation to detect that the index may be stale.
+#[derive(Debug, Clone, Copy)]
+pub struct Versioned<'a, T> {
+    a: &'a [T],
+    version: u64,
+}
+
+impl<'a, T> Versioned<'a, T> {
+    /// Wrap `a`, which must be sorted, as of `version`.
+    pub fn new(a: &'a [T], version: u64) -> Self {
+        Versioned { a, version }
+    }
+
+    /// Return the version the slice was wrapped at.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Return the wrapped slice.
+    pub fn as_slice(&self) -> &'a [T] {
+        self.a
+    }
+
+    /// Return [`bisect_left`] of `x` in the slice along with the version it was computed
+    /// against.
+    pub fn bisect_left(&self, x: &T) -> (usize, u64)
+    where
+        T: PartialOrd,
+    {
+        (bisect_left(self.a, x), self.version)
+    }
+}
+
+/// Return the index of the `n`-th occurrence of `x` in `a`, counting from `0`, assuming `a` is
+/// sorted, or `None` if `a` has `n` or fewer elements equal to `x`.
+///
+/// The index is `bisect_left(a, x) + n`, kept only if it is still inside [`equal_range`].
+pub fn nth_occurrence<T>(a: &[T], x: &T, n: usize) -> Option<usize>
+where
+    T: PartialOrd,
+{
+    let run = equal_range(a, x);
+    run.start.checked_add(n).filter(|&i| i < run.end)
+}
+
+/// Return the elements of `a` in the closed interval `[low, high]`, assuming `a` is sorted.
+///
+/// This is `&a[bisect_left(a, &low)..bisect_right(a, &high)]`, without allocating. An interval
+/// with `low > high` is empty, as is one outside the values of `a`.
+pub fn closed_range_i64(a: &[i64], low: i64, high: i64) -> &[i64] {
+    if low > high {
+        return &[];
+    }
+    let lo = bisect_left(a, &low);
+    let hi = bisect_right_slice(a, &high, lo..);
+    &a[lo..hi]
+}
+
+/// Return the index where a value should be inserted in `a`, assuming it sorted, according to
+/// a comparator function that may abort the search.
+///
+/// `f` follows the convention of [`bisect_left_by`], except that returning `None` stops the
+/// search, e.g. on a timeout or a cancellation request. The result is then `Err(lo)`, the lower
+/// end of the bracket known to contain the answer so far: every element before `lo` is known to
+/// be `Less`. Otherwise the result is `Ok` of the index [`bisect_left_by`] would return.
+pub fn bisect_left_abortable<T, F>(a: &[T], mut f: F) -> Result<usize, usize>
+where
+    F: FnMut(&T) -> Option<Ordering>,
+{
+    let (mut lo, mut hi) = (0, a.len());
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match f(&a[mid]) {
+            Some(Ordering::Less) => lo = mid + 1,
+            Some(_) => hi = mid,
+            None => return Err(lo),
+        }
+    }
+    Ok(lo)
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is sorted, after
+/// converting `x` into the units of `a` with `map`.
+///
+/// This is the mirror of [`bisect_left_mapped`]: `map` is applied once, to the target, instead
+/// of to every probed element, so it is the cheaper choice when the conversion goes naturally
+/// from the query to the slice. `map` must be monotone non-decreasing for the result to mean
+/// the same as converting `a` the other way.
+pub fn bisect_left_target_map<T, U, M>(a: &[T], x: &U, map: M) -> usize
+where
+    T: PartialOrd,
+    M: Fn(&U) -> T,
+{
+    bisect_left(a, &map(x))
+}
+
+/// Return the elements `new` has that `old` doesn't, and those `old` has that `new` doesn't,
+/// assuming both are sorted.
+///
+/// The result is `(added, removed)`, each sorted, from one linear walk over both slices.
+/// Duplicates are counted as a multiset: an element appearing `m` times in `old` and `n` times
+/// in `new` appears `n - m` times in `added` if `n > m`, or `m - n` times in `removed` if
+/// `m > n`.
+pub fn diff_sorted<T>(old: &[T], new: &[T]) -> (Vec<T>, Vec<T>)
+where
+    T: Ord + Clone,
+{
+    let (mut added, mut removed) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        match old[i].cmp(&new[j]) {
+            Ordering::Less => {
+                removed.push(old[i].clone());
+                i += 1;
+            }
+            Ordering::Greater => {
+                added.push(new[j].clone());
+                j += 1;
+            }
+            Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    removed.extend_from_slice(&old[i..]);
+    added.extend_from_slice(&new[j..]);
+    (added, removed)
+}
+
+/// Return the index where `x` should be inserted in `a`, comparing values rounded to
+/// `decimals` decimal places, so that `x` finds the start of its rounding bucket.
+///
+/// Each value `v` is compared as `(v * 10^decimals).round()`, keeping full precision in `a`.
+/// Rounding is monotone, so a slice sorted by raw value is also sorted by rounded value, and
+/// the result is [`bisect_left`] on the rounded slice: every element rounding to the same
+/// bucket as `x` is at or after the result.
+pub fn bisect_left_rounded(a: &[f64], x: f64, decimals: u32) -> usize {
+    let scale = 10_f64.powi(decimals as i32);
+    let x = (x * scale).round();
+    search(0, a.len(), |i| (a[i] * scale).round() < x)
+}
+
+/// Return the smallest index `i` such that the total of `weights[..=i]` is at least
+/// `target_cumweight`, for selecting from the sorted `values` by weight.
+///
+/// With `target_cumweight` half the total weight, `values[i]` is the weighted median. The
+/// weights are summed into a cumulative array, which is then bisected. The result is
+/// `values.len()` if the total weight is below `target_cumweight`. Weights must be
+/// non-negative, so that the cumulative array is sorted.
+///
+/// # Panics
+///
+/// Panics if `values` and `weights` have different lengths.
+pub fn weighted_bisect(values: &[f64], weights: &[f64], target_cumweight: f64) -> usize {
+    assert_eq!(
+        values.len(),
+        weights.len(),
+        "values and weights must have the same length"
+    );
+    let cumulative: Vec<f64> = weights
+        .iter()
+        .scan(0.0, |total, w| {
+            *total += w;
+            Some(*total)
+        })
+        .collect();
+    bisect_left(&cumulative, &target_cumweight)
+}
+
+/// Return [`bisect_right`] of `x` in `a`, assuming `a` is sorted, as an index that is always
+/// safe to use with `a`, or `None` if the insertion point is at the end.
+///
+/// `Some(i)` means `a[i]` is the first element greater than `x`. The plain [`bisect_right`]
+/// result can equal `a.len()`, so indexing with it panics when `x` is at or above every element.
+pub fn bisect_right_clamped_index<T>(a: &[T], x: &T) -> Option<usize>
+where
+    T: PartialOrd,
+{
+    Some(bisect_right(a, x)).filter(|&i| i < a.len())
+}
+
+/// Return the lowest index of the run of elements of `a` for which `f` returns `Equal`, or the
+/// position that run would start at if it's empty.
+///
+/// `f` returns how an element compares to the target in the order `a` is sorted by, which may
+/// be descending: `a` must be `Less` on some prefix, then `Equal`, then `Greater`. "First" and
+/// [`bisect_last_by`]'s "last" always refer to index position, whichever way values run. For an
+/// ascending `a` compared with `|e| e.cmp(&x)`, this is [`bisect_left_by`].
+pub fn bisect_first_by<T, F>(a: &[T], f: F) -> usize
+where
+    F: FnMut(&T) -> Ordering,
+{
+    lower_bound_by(a, f)
+}
+
+/// Return one past the highest index of the run of elements of `a` for which `f` returns
+/// `Equal`, or the position that run would start at if it's empty.
+///
+/// `f` follows the same convention as for [`bisect_first_by`], so the same closure can be
+/// passed to both and `bisect_first_by(a, f)..bisect_last_by(a, f)` is the run. For an
+/// ascending `a` compared with `|e| e.cmp(&x)`, this is [`bisect_right`].
+pub fn bisect_last_by<T, F>(a: &[T], f: F) -> usize
+where
+    F: FnMut(&T) -> Ordering,
+{
+    upper_bound_by(a, f)
+}
+
+/// Return the index of a live entry of `a` whose referent has key `key`, assuming `a` is
+/// sorted by the keys of its referents, skipping entries whose referent has been dropped.
+///
+/// Each probe upgrades its `Weak`. A dead one is treated as absent: the probe moves to the
+/// nearest live entry still inside the current bracket, so a bracket of only dead entries ends
+/// the search. With many dead entries interspersed this degrades towards a linear scan, and a
+/// live match is still found as long as the live entries are sorted, so compact `a` now and then
+/// with `a.retain(|w| w.strong_count() > 0)`. The result is `None` if no live entry matches;
+/// if several do, it is the leftmost of them.
+pub fn bisect_left_weak<T, K, F>(a: &[Weak<T>], key: &K, key_of: F) -> Option<usize>
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    let (mut lo, mut hi) = (0, a.len());
+    let mut found = None;
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let live = (mid..hi)
+            .chain((lo..mid).rev())
+            .find_map(|i| a[i].upgrade().map(|t| (i, t)));
+        let (i, t) = match live {
+            Some(live) => live,
+            None => break,
+        };
+        match key_of(&t).cmp(key) {
+            Ordering::Less => lo = i + 1,
+            Ordering::Greater => hi = i,
+            Ordering::Equal => {
+                // Keep looking left of a match for an earlier live one
+                found = Some(i);
+                hi = i;
+            }
+        }
+    }
+    found
+}
+
+/// Return the worst-case number of comparisons against the target that a search of a slice of
+/// length `len` makes, `⌈log2(len + 1)⌉`.
+///
+/// This bounds the descent of every search, e.g. [`bisect_left`], [`bisect_right`] and their
+/// `_by` variants: each probe at least halves the bracket. It is also the smallest budget for
+/// which [`bisect_left_budgeted`] always succeeds.
+pub fn max_probes(len: usize) -> usize {
+    8 * std::mem::size_of::<usize>() - len.leading_zeros() as usize
+}
+
+/// Return the index where `x` should be inserted in `a`, as [`bisect_left`], along with the
+/// number of comparisons against `x` the descent made.
+///
+/// The count never exceeds [`max_probes`] of `a.len()`.
+pub fn bisect_left_counted<T>(a: &[T], x: &T) -> (usize, usize)
+where
+    T: PartialOrd,
+{
+    let mut probes = 0;
+    let i = search(0, a.len(), |mid| {
+        probes += 1;
+        a[mid] < *x
+    });
+    (i, probes)
+}
+
+/// Insert `x` in `a`, keeping it sorted assuming `a` is sorted, at whichever end of the run of
+/// elements equal to `x` is nearer an end of `a`, and return the insertion index.
+///
+/// Any position within the run keeps `a` sorted. Of the run's two ends, the one minimizing
+/// `min(i, a.len() - i)` is chosen, preferring the right end on a tie. That is the number of
+/// elements a double-ended buffer such as `VecDeque` shifts for an insertion at `i`, so the
+/// index also suits mirroring the insertion into one; `Vec::insert` itself always shifts the
+/// `a.len() - i` elements after `i`.
+pub fn insort_min_shift<T>(a: &mut Vec<T>, x: T) -> usize
+where
+    T: Ord,
+{
+    let run = equal_range(a, &x);
+    let cost = |i: usize| std::cmp::min(i, a.len() - i);
+    let i = if cost(run.start) < cost(run.end) {
+        run.start
+    } else {
+        run.end
+    };
+    a.insert(i, x);
+    i
+}
+
+/// Split `a` at `threshold`, assuming `a` is sorted, into the elements below it and those at or
+/// above it, along with the split index.
+///
+/// The result is `(i, &a[..i], &a[i..])` with `i` from [`bisect_left`], so elements equal to
+/// `threshold` go to the second part. Use [`threshold_split_inclusive`] to keep them in the
+/// first.
+pub fn threshold_split<'a, T>(a: &'a [T], threshold: &T) -> (usize, &'a [T], &'a [T])
+where
+    T: PartialOrd,
+{
+    let i = bisect_left(a, threshold);
+    (i, &a[..i], &a[i..])
+}
+
+/// Split `a` at `threshold`, assuming `a` is sorted, into the elements at or below it and those
+/// above it, along with the split index.
+///
+/// Like [`threshold_split`], but with `i` from [`bisect_right`], so elements equal to
+/// `threshold` go to the first part.
+pub fn threshold_split_inclusive<'a, T>(a: &'a [T], threshold: &T) -> (usize, &'a [T], &'a [T])
+where
+    T: PartialOrd,
+{
+    let i = bisect_right(a, threshold);
+    (i, &a[..i], &a[i..])
+}
+
+/// A key that [`bisect_left_interp`] can estimate positions of by linear interpolation.
+pub trait Interpolatable {
+    /// Return the key as an `f64`, preserving its order. Precision may be lost for large
+    /// integers, which only makes the position estimates less accurate.
+    fn to_f64(&self) -> f64;
+}
+
+macro_rules! interpolatable {
+    ($($t:ty),*) => {
+        $(
+            impl Interpolatable for $t {
+                fn to_f64(&self) -> f64 {
+                    *self as f64
+                }
+            }
+        )*
+    };
+}
+
+interpolatable!(i32, i64, u32, u64, usize, f32, f64);
+
+/// Return the index where `x` should be inserted in `a`, like [`bisect_left`], probing where
+/// linear interpolation between the ends of the bracket puts `x`.
+///
+/// On roughly uniformly spaced keys this takes about `O(log log n)` probes. Interpolated probes
+/// alternate with plain midpoints, so skewed keys cost at most about twice the probes of
+/// [`bisect_left`] rather than degrading to a linear scan. The result is always the same as
+/// [`bisect_left`], since every probe is inside the bracket.
+pub fn bisect_left_interp<T>(a: &[T], x: &T) -> usize
+where
+    T: Interpolatable + PartialOrd,
+{
+    let (mut lo, mut hi) = (0, a.len());
+    let xv = x.to_f64();
+    let mut interpolate = true;
+    while lo < hi {
+        let mut mid = lo + (hi - lo) / 2;
+        if interpolate {
+            let (lv, hv) = (a[lo].to_f64(), a[hi - 1].to_f64());
+            let t = (xv - lv) / (hv - lv);
+            if t.is_finite() {
+                let offset = (t.clamp(0.0, 1.0) * (hi - 1 - lo) as f64) as usize;
+                mid = lo + offset;
+            }
+        }
+        interpolate = !interpolate;
+        if a[mid] < *x {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Remove and return the element of `a` equal to `x`, or the element nearest to it if there is
+/// none, assuming `a` is sorted; `None` only if `a` is empty.
+///
+/// If `a` contains `x`, the *leftmost* equal element is removed. Otherwise the candidates are
+/// the neighbours on either side of the insertion point, and the one for which
+/// `distance(e, x)` is smaller is removed, the smaller of the two on a tie. Either way `a` stays
+/// sorted. `distance` is supplied by the caller so it can't overflow where `e - x` would, e.g.
+/// `|e, x| e.abs_diff(*x)` for integers.
+pub fn bisect_remove_target<T, F, D>(a: &mut Vec<T>, x: &T, mut distance: F) -> Option<T>
+where
+    T: PartialOrd,
+    F: FnMut(&T, &T) -> D,
+    D: PartialOrd,
+{
+    let i = bisect_left(a, x);
+    let index = match (i.checked_sub(1), a.get(i)) {
+        (_, Some(e)) if *e == *x => i,
+        (Some(before), Some(after)) => {
+            if distance(after, x) < distance(&a[before], x) {
+                i
+            } else {
+                before
+            }
+        }
+        (None, Some(_)) => i,
+        (Some(before), None) => before,
+        (None, None) => return None,
+    };
+    Some(a.remove(index))
+}
+
+/// Return the index of the first element of `a` for which `primary` returns `Equal` and
+/// `secondary_filter` returns `true`, assuming `a` is sorted according to `primary`.
+///
+/// `primary` follows the convention of [`bisect_left_by`] and narrows the search to its equal
+/// run, which is then scanned in order with `secondary_filter`, e.g. a per-component tolerance
+/// check. The result is `None` if the run is empty or no element of it passes.
+pub fn bisect_left_banded<T, F, G>(a: &[T], primary: F, secondary_filter: G) -> Option<usize>
+where
+    F: FnMut(&T) -> Ordering,
+    G: Fn(&T) -> bool,
+{
+    let run = equal_range_by(a, primary);
+    let start = run.start;
+    a[run].iter().position(secondary_filter).map(|i| start + i)
+}
+
+/// Return the final `(lo, hi)` bracket of the descent of [`bisect_left`] for `x` in `a`,
+/// assuming `a` is sorted.
+///
+/// The descent runs until the bracket is empty, so `lo == hi == bisect_left(a, x)`; the
+/// invariant is that every element before `lo` is less than `x` and none from `hi` on is. Build
+/// on that instead of reimplementing the loop, or use [`bisect_left_budgeted`] to stop it early
+/// with a wider bracket.
+pub fn bracket_left<T>(a: &[T], x: &T) -> (usize, usize)
+where
+    T: PartialOrd,
+{
+    let i = search(0, a.len(), |mid| a[mid] < *x);
+    (i, i)
+}
+
+/// How a [`StreamingInsort`] grows its buffer when it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrowthPolicy {
+    /// Leave growth to `Vec`, which grows geometrically, so `n` insertions reallocate
+    /// `O(log n)` times with unpredictable sizes.
+    Geometric,
+    /// Grow by exactly this many elements at a time, so `n` insertions reallocate at most
+    /// `⌈n / chunk⌉` times, each a predictable size.
+    Chunked(usize),
+}
+
+/// Counters reported by [`StreamingInsort::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InsortStats {
+    /// The number of times the buffer had to grow.
+    pub reallocations: usize,
+    /// The total number of elements moved to make room for insertions.
+    pub shifted: usize,
+}
+
+/// A sorted `Vec` fed by a stream of insertions, with control over and counters for the cost
+/// of each insertion.
+#[derive(Debug, Clone)]
+pub struct StreamingInsort<T> {
+    a: Vec<T>,
+    policy: GrowthPolicy,
+    stats: InsortStats,
+}
+
+impl<T> StreamingInsort<T>
+where
+    T: Ord,
+{
+    /// Create an empty sorted buffer growing according to `policy`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `policy` is `Chunked(0)`.
+    pub fn new(policy: GrowthPolicy) -> Self {
+        assert!(
+            policy != GrowthPolicy::Chunked(0),
+            "chunk size must be nonzero"
+        );
+        StreamingInsort {
+            a: Vec::new(),
+            policy,
+            stats: InsortStats::default(),
+        }
+    }
+
+    /// Insert `x` as [`insort_right`] would, growing the buffer first if it is full, and return
+    /// its index.
+    pub fn insert(&mut self, x: T) -> usize {
+        if self.a.len() == self.a.capacity() {
+            match self.policy {
+                GrowthPolicy::Geometric => self.a.reserve(1),
+                GrowthPolicy::Chunked(chunk) => self.a.reserve_exact(chunk),
+            }
+            self.stats.reallocations += 1;
+        }
+        let i = bisect_right(&self.a, &x);
+        self.stats.shifted += self.a.len() - i;
+        self.a.insert(i, x);
+        i
+    }
+
+    /// Return the counters accumulated so far.
+    pub fn stats(&self) -> InsortStats {
+        self.stats
+    }
+
+    /// Return the sorted elements.
+    pub fn as_slice(&self) -> &[T] {
+        &self.a
+    }
+
+    /// Return the sorted elements as a `Vec`.
+    pub fn into_vec(self) -> Vec<T> {
+        self.a
+    }
+}
+
+/// Return the permutation that sorts `a`: the indices of `a` in order of their elements.
+///
+/// The permutation is built by inserting each index in turn, after any earlier indices of equal
+/// elements, so the sort is stable. Each insertion bisects in `O(log n)` comparisons but may
+/// shift `O(n)` indices, so this is `O(n²)` worst case, and best suited to small or
+/// nearly-sorted inputs.
+pub fn argsort_insort<T>(a: &[T]) -> Vec<usize>
+where
+    T: Ord,
+{
+    let mut order: Vec<usize> = Vec::with_capacity(a.len());
+    for (i, x) in a.iter().enumerate() {
+        let at = upper_bound_by(&order, |&j| a[j].cmp(x));
+        order.insert(at, i);
+    }
+    order
+}
+
+/// Return the first index `i` of `fingerprints` equal to `fp` for which `verify(i)` holds,
+/// assuming `fingerprints` is sorted.
+///
+/// Equal fingerprints may come from different keys that collide, so `verify` checks each
+/// candidate in the run of `fp`, in order, against the real key, e.g. one stored in a parallel
+/// array at the same index. The result is `None` if no candidate verifies.
+pub fn bisect_fingerprint<F>(fingerprints: &[u64], fp: u64, verify: F) -> Option<usize>
+where
+    F: Fn(usize) -> bool,
+{
+    equal_range(fingerprints, &fp).find(|&i| verify(i))
+}
+
+/// Return how many elements at the front of `large` are at or below `pivot`, assuming `large`
+/// is sorted: how far a galloping merge can advance through `large` in one jump.
+///
+/// This is [`bisect_right`], named for merging a short sorted run into a long one. Rather than
+/// comparing element by element, each element of the short run jumps past the whole block of
+/// `large` that goes before it, in `O(log n)` comparisons, and the block is copied in bulk. A
+/// short run of `m` sparse values is then merged with `O(m log n)` comparisons instead of
+/// `O(m + n)`; only the copying stays linear.
+///
+/// # Examples
+///
+/// ```
+/// use bisection::advance_past;
+///
+/// let long: Vec<u32> = (0..1000).collect();
+/// let short = [10, 500, 500, 2000];
+///
+/// let mut merged = Vec::with_capacity(long.len() + short.len());
+/// let mut rest = &long[..];
+/// for &pivot in &short {
+///     let k = advance_past(rest, &pivot);
+///     merged.extend_from_slice(&rest[..k]);
+///     merged.push(pivot);
+///     rest = &rest[k..];
+/// }
+/// merged.extend_from_slice(rest);
+///
+/// let mut expected = [&long[..], &short[..]].concat();
+/// expected.sort();
+/// assert_eq!(merged, expected);
+/// ```
+pub fn advance_past<T>(large: &[T], pivot: &T) -> usize
+where
+    T: Ord,
+{
+    bisect_right(large, pivot)
+}
+
+/// Return the index where `x_scaled` should be inserted in `a`, assuming `a` is a sorted slice of
+/// fixed-point values, such as amounts in cents, with the same scale as `x_scaled`.
+///
+/// This is [`bisect_left`] on `i64`, as a named entry point for fixed-point data: all
+/// comparisons are exact integer comparisons, with none of the rounding of a float
+/// representation. Use [`bisect_left_decimal`] to search for a float value.
+pub fn bisect_left_fixed_point(a: &[i64], x_scaled: i64) -> usize {
+    bisect_left(a, &x_scaled)
+}
+
+/// Insert `x_scaled` in `a`, keeping it sorted assuming `a` is a sorted slice of fixed-point
+/// values with the same scale as `x_scaled`, and return the index it was inserted at.
+///
+/// This is [`insort_right`] on `i64`, with exact comparisons as for
+/// [`bisect_left_fixed_point`]. It goes after any equal values.
+pub fn insort_right_fixed_point(a: &mut Vec<i64>, x_scaled: i64) -> usize {
+    let i = bisect_right(a, &x_scaled);
+    a.insert(i, x_scaled);
+    i
+}
+
+/// Return the index where `x` should be inserted in `a`, assuming `a` is a sorted slice of
+/// fixed-point values with `scale` decimal places, e.g. `2` for cents.
+///
+/// `x` is converted to the fixed-point scale first, as `x * 10^scale` rounded to the nearest
+/// integer with ties to even, so `0.125` at scale `2` is `12` and `0.135` is `14`. This is
+/// the rounding of the binary value of `x`. A decimal literal that isn't exactly representable
+/// may not be a tie at all: the `f64` nearest `1.005` is slightly below it, so it rounds to
+/// `100`. Values beyond the range of `i64` saturate. The search itself is then the exact
+/// [`bisect_left_fixed_point`].
+///
+/// # Panics
+///
+/// Panics if `x` is NaN.
+pub fn bisect_left_decimal(a: &[i64], x: f64, scale: u32) -> usize {
+    assert!(!x.is_nan(), "cannot convert NaN to fixed point");
+    let v = x * 10_f64.powi(scale as i32);
+    let floor = v.floor();
+    let rounded = match (v - floor).partial_cmp(&0.5) {
+        Some(Ordering::Less) => floor,
+        Some(Ordering::Greater) => floor + 1.0,
+        _ if floor % 2.0 == 0.0 => floor,
+        _ => floor + 1.0,
+    };
+    bisect_left_fixed_point(a, rounded as i64)
+}
+
+/// Insert `x` in `a`, keeping it strictly increasing assuming it is, and return the index it was
+/// inserted at, unless `a` already contains an element equal to `x`.
+///
+/// In that case `a` isn't modified, and the index of the existing element is returned along
+/// with `x` itself as `Err((i, x))`, so the caller can still use the rejected value, e.g. to
+/// merge it into `a[i]`. [`assert_unique_sorted`] checks the same invariant after the fact.
+pub fn insort_strict<T>(a: &mut Vec<T>, x: T) -> Result<usize, (usize, T)>
+where
+    T: Ord,
+{
+    let i = bisect_left(a, &x);
+    if a.get(i) == Some(&x) {
+        return Err((i, x));
+    }
+    a.insert(i, x);
+    Ok(i)
+}
+
+/// Return the index where `target` should be inserted in the key column `keys` of a sorted
+/// structure-of-arrays table, assuming `keys` is sorted.
+///
+/// This is [`bisect_left`] on the key column. The index is equally valid in every other column
+/// of the table, since they are kept aligned with it; see [`soa_equal_range`].
+pub fn bisect_left_soa<K>(keys: &[K], target: &K) -> usize
+where
+    K: PartialOrd,
+{
+    bisect_left(keys, target)
+}
+
+/// Return the range of rows with key `target` in a sorted structure-of-arrays table, given its
+/// key column `keys`, assuming `keys` is sorted.
+///
+/// The range is [`equal_range`] on the key column, and slices every column of the table.
+///
+/// # Examples
+///
+/// A table of readings keyed by sensor id, with the ids and values in separate columns, kept
+/// aligned with [`insort_right_soa`]:
+///
+/// ```
+/// use bisection::{insort_right_soa, soa_equal_range};
+///
+/// let mut ids: Vec<u32> = Vec::new();
+/// let mut values: Vec<f64> = Vec::new();
+/// for &(id, value) in &[(7, 0.5), (3, 1.5), (7, 2.5), (9, 3.5)] {
+///     insort_right_soa(&mut ids, &mut values, id, value);
+/// }
+///
+/// let rows = soa_equal_range(&ids, &7);
+/// assert_eq!(&ids[rows.clone()], &[7, 7]);
+/// assert_eq!(&values[rows], &[0.5, 2.5]);
+/// ```
+pub fn soa_equal_range<K>(keys: &[K], target: &K) -> Range<usize>
+where
+    K: PartialOrd,
+{
+    equal_range(keys, target)
+}
+
+/// Insert a row with key `key` and payload `value` into a sorted structure-of-arrays table, in
+/// both its key column `keys` and its payload column `values`, and return the row index.
+///
+/// The row goes after any rows with an equal key, so equal keys keep insertion order, and both
+/// columns stay aligned.
+///
+/// # Panics
+///
+/// Panics if the columns have different lengths.
+pub fn insort_right_soa<K, V>(keys: &mut Vec<K>, values: &mut Vec<V>, key: K, value: V) -> usize
+where
+    K: PartialOrd,
+{
+    assert_eq!(
+        keys.len(),
+        values.len(),
+        "columns must have the same length"
+    );
+    let i = bisect_right(keys, &key);
+    keys.insert(i, key);
+    values.insert(i, value);
+    i
+}
+
+/// Convert bounds to a `(lo, hi)`  pair for indexing into a slice of `a`.
+///
+/// # Panics
+///
+/// Panics if `within` is out of bounds of `a`.
+#[inline]
+fn bounds_to_indices<T, I>(a: &[T], within: I) -> (usize, usize)
+where
+    I: RangeBounds<usize>,
+{
+    // TODO: Panics
+    let lo = match within.start_bound() {
+        Unbounded => 0,
+        Included(i) => *i,
+        Excluded(i) => i.checked_add(1).expect("bound index overflow"),
+    };
+
+    let hi = match within.end_bound() {
+        Unbounded => a.len(),
+        Included(i) => i.checked_add(1).expect("bound index overflow"),
+        Excluded(i) => *i,
+    };
+
+    if hi > a.len() {
+        panic!("index out of bounds")
+    }
+
+    (lo, hi)
+}
+
+/// Return the first index in `lo..hi` for which `go_right` is `false`, assuming `go_right` is
+/// `true` for a (possibly empty) prefix of the range and `false` for the rest.
+///
+/// This is the core descent shared by every search. The `recursive` feature swaps the loop for
+/// an equivalent recursive formulation so the two can be benchmarked against each other. Both
+/// take midpoints as `lo + (hi - lo) / 2`, which can't overflow however large `hi` is.
+///
+/// The loop is small and generic over the probe, so it is always inlined: that lets the probe
+/// closure be inlined into it in turn, leaving no calls in the hot loop of a caller that
+/// bisects for many targets.
+#[cfg(not(feature = "recursive"))]
+#[inline(always)]
+fn search<F>(lo: usize, hi: usize, go_right: F) -> usize
+where
+    F: FnMut(usize) -> bool,
+{
+    search_iterative(lo, hi, go_right)
+}
+
+#[cfg(feature = "recursive")]
+#[inline(always)]
+fn search<F>(lo: usize, hi: usize, mut go_right: F) -> usize
+where
+    F: FnMut(usize) -> bool,
+{
+    search_recursive(lo, hi, &mut go_right)
+}
+
+/// Return the first index in `lo..hi` for which `go_right` is `false`, with the same
+/// assumptions as `search`, probing exponentially further from `lo` before bisecting.
+///
+/// This takes `O(log k)` probes when the answer is `k` past `lo`, so it beats `search` when the
+/// answer is expected to be close to `lo`.
+fn gallop<F>(lo: usize, hi: usize, mut go_right: F) -> usize
+where
+    F: FnMut(usize) -> bool,
+{
+    let (mut start, mut probe, mut step) = (lo, lo, 1_usize);
+    while probe < hi && go_right(probe) {
+        start = probe + 1;
+        probe = probe.saturating_add(step);
+        step = step.saturating_mul(2);
+    }
+    search(start, std::cmp::min(probe, hi), go_right)
+}
+
+/// Return the first index in `lo..hi` for which `go_right` is `false`, with the same
+/// assumptions as `search`, probing exponentially further back from `hi` before bisecting.
+///
+/// This is the mirror image of `gallop`, for when the answer is expected to be close to `hi`.
+fn gallop_back<F>(lo: usize, hi: usize, mut go_right: F) -> usize
+where
+    F: FnMut(usize) -> bool,
+{
+    let (mut end, mut step) = (hi, 1_usize);
+    while end > lo {
+        let probe = end - std::cmp::min(step, end - lo);
+        if go_right(probe) {
+            return search(probe + 1, end, go_right);
+        }
+        end = probe;
+        step = step.saturating_mul(2);
+    }
+    lo
+}
+
+#[cfg(any(not(feature = "recursive"), test))]
+#[inline(always)]
+fn search_iterative<F>(mut lo: usize, mut hi: usize, mut go_right: F) -> usize
+where
+    F: FnMut(usize) -> bool,
+{
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if go_right(mid) {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    lo
+}
+
+/// Each call is in tail position, so LLVM is free to compile this down to the same loop as
+/// `search_iterative`. Rust doesn't *guarantee* that, but the depth is bounded by
+/// `log2(hi - lo)` either way.
+#[cfg(any(feature = "recursive", test))]
+fn search_recursive<F>(lo: usize, hi: usize, go_right: &mut F) -> usize
+where
+    F: FnMut(usize) -> bool,
+{
+    if lo >= hi {
+        return lo;
+    }
+    let mid = lo + (hi - lo) / 2;
+    if go_right(mid) {
+        search_recursive(mid + 1, hi, go_right)
+    } else {
+        search_recursive(lo, mid, go_right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+    use std::iter::FromIterator;
+
+    #[derive(Clone, Debug)]
+    struct BisectTest<T: 'static>
+    where
+        T: PartialOrd,
+    {
+        name: &'static str,
+        a: &'static [T],
+        x: T,
+        expected_index: usize,
+    }
+
+    #[derive(Debug, Clone)]
+    enum TestDirection {
+        Left,
+        Right,
+    }
+
+    type TestCollection<T> = &'static [BisectTest<T>];
+
+    macro_rules! t {
+        ($name:ident, $a:expr, $x:expr, $expected_index:expr) => {
+            BisectTest {
+                name: stringify!($name),
+                a: $a,
+                x: $x,
+                expected_index: $expected_index,
+            }
+        };
+    }
+
+    const RIGHT_INT_CASES: TestCollection<i32> = &[
+        t!(ints_right_0, &[], 1, 0),
+        t!(ints_right_1, &[1], 0, 0),
+        t!(ints_right_2, &[1], 1, 1),
+        t!(ints_right_3, &[1], 2, 1),
+        t!(ints_right_4, &[1, 1], 0, 0),
+        t!(ints_right_5, &[1, 1], 1, 2),
+        t!(ints_right_6, &[1, 1], 2, 2),
+        t!(ints_right_7, &[1, 1, 1], 0, 0),
+        t!(ints_right_8, &[1, 1, 1], 1, 3),
+        t!(ints_right_9, &[1, 1, 1], 2, 3),
+        t!(ints_right_10, &[1, 1, 1, 1], 0, 0),
+        t!(ints_right_11, &[1, 1, 1, 1], 1, 4),
+        t!(ints_right_12, &[1, 1, 1, 1], 2, 4),
+        t!(ints_right_13, &[1, 2], 0, 0),
+        t!(ints_right_14, &[1, 2], 1, 1),
+        t!(ints_right_15, &[1, 2], 2, 2),
+        t!(ints_right_16, &[1, 2], 3, 2),
         t!(ints_right_17, &[1, 1, 2, 2], 0, 0),
         t!(ints_right_18, &[1, 1, 2, 2], 1, 2),
         t!(ints_right_19, &[1, 1, 2, 2], 2, 4),
@@ -411,360 +4862,4193 @@ mod tests {
         t!(ints_right_31, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 5, 10),
     ];
 
-    const LEFT_INT_CASES: TestCollection<i32> = &[
-        t!(ints_left_0, &[], 1, 0),
-        t!(ints_left_1, &[1], 0, 0),
-        t!(ints_left_2, &[1], 1, 0),
-        t!(ints_left_3, &[1], 2, 1),
-        t!(ints_left_4, &[1, 1], 0, 0),
-        t!(ints_left_5, &[1, 1], 1, 0),
-        t!(ints_left_6, &[1, 1], 2, 2),
-        t!(ints_left_7, &[1, 1, 1], 0, 0),
-        t!(ints_left_8, &[1, 1, 1], 1, 0),
-        t!(ints_left_9, &[1, 1, 1], 2, 3),
-        t!(ints_left_10, &[1, 1, 1, 1], 0, 0),
-        t!(ints_left_11, &[1, 1, 1, 1], 1, 0),
-        t!(ints_left_12, &[1, 1, 1, 1], 2, 4),
-        t!(ints_left_13, &[1, 2], 0, 0),
-        t!(ints_left_14, &[1, 2], 1, 0),
-        t!(ints_left_15, &[1, 2], 2, 1),
-        t!(ints_left_16, &[1, 2], 3, 2),
-        t!(ints_left_17, &[1, 1, 2, 2], 0, 0),
-        t!(ints_left_18, &[1, 1, 2, 2], 1, 0),
-        t!(ints_left_19, &[1, 1, 2, 2], 2, 2),
-        t!(ints_left_20, &[1, 1, 2, 2], 3, 4),
-        t!(ints_left_21, &[1, 2, 3], 0, 0),
-        t!(ints_left_22, &[1, 2, 3], 1, 0),
-        t!(ints_left_23, &[1, 2, 3], 2, 1),
-        t!(ints_left_24, &[1, 2, 3], 3, 2),
-        t!(ints_left_25, &[1, 2, 3], 4, 3),
-        t!(ints_left_26, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 0, 0),
-        t!(ints_left_27, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 1, 0),
-        t!(ints_left_28, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 2, 1),
-        t!(ints_left_29, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 3, 3),
-        t!(ints_left_30, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 4, 6),
-        t!(ints_left_31, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 5, 10),
-    ];
+    const LEFT_INT_CASES: TestCollection<i32> = &[
+        t!(ints_left_0, &[], 1, 0),
+        t!(ints_left_1, &[1], 0, 0),
+        t!(ints_left_2, &[1], 1, 0),
+        t!(ints_left_3, &[1], 2, 1),
+        t!(ints_left_4, &[1, 1], 0, 0),
+        t!(ints_left_5, &[1, 1], 1, 0),
+        t!(ints_left_6, &[1, 1], 2, 2),
+        t!(ints_left_7, &[1, 1, 1], 0, 0),
+        t!(ints_left_8, &[1, 1, 1], 1, 0),
+        t!(ints_left_9, &[1, 1, 1], 2, 3),
+        t!(ints_left_10, &[1, 1, 1, 1], 0, 0),
+        t!(ints_left_11, &[1, 1, 1, 1], 1, 0),
+        t!(ints_left_12, &[1, 1, 1, 1], 2, 4),
+        t!(ints_left_13, &[1, 2], 0, 0),
+        t!(ints_left_14, &[1, 2], 1, 0),
+        t!(ints_left_15, &[1, 2], 2, 1),
+        t!(ints_left_16, &[1, 2], 3, 2),
+        t!(ints_left_17, &[1, 1, 2, 2], 0, 0),
+        t!(ints_left_18, &[1, 1, 2, 2], 1, 0),
+        t!(ints_left_19, &[1, 1, 2, 2], 2, 2),
+        t!(ints_left_20, &[1, 1, 2, 2], 3, 4),
+        t!(ints_left_21, &[1, 2, 3], 0, 0),
+        t!(ints_left_22, &[1, 2, 3], 1, 0),
+        t!(ints_left_23, &[1, 2, 3], 2, 1),
+        t!(ints_left_24, &[1, 2, 3], 3, 2),
+        t!(ints_left_25, &[1, 2, 3], 4, 3),
+        t!(ints_left_26, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 0, 0),
+        t!(ints_left_27, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 1, 0),
+        t!(ints_left_28, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 2, 1),
+        t!(ints_left_29, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 3, 3),
+        t!(ints_left_30, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 4, 6),
+        t!(ints_left_31, &[1, 2, 2, 3, 3, 3, 4, 4, 4, 4], 5, 10),
+    ];
+
+    const RIGHT_FLOAT_CASES: TestCollection<f32> = &[
+        t!(floats_right_0, &[], 1.0, 0),
+        t!(floats_right_1, &[1.0], 0.0, 0),
+        t!(floats_right_2, &[1.0], 1.0, 1),
+        t!(floats_right_3, &[1.0], 2.0, 1),
+        t!(floats_right_4, &[1.0, 1.0], 0.0, 0),
+        t!(floats_right_5, &[1.0, 1.0], 1.0, 2),
+        t!(floats_right_6, &[1.0, 1.0], 2.0, 2),
+        t!(floats_right_7, &[1.0, 1.0, 1.0], 0.0, 0),
+        t!(floats_right_8, &[1.0, 1.0, 1.0], 1.0, 3),
+        t!(floats_right_9, &[1.0, 1.0, 1.0], 2.0, 3),
+        t!(floats_right_10, &[1.0, 1.0, 1.0, 1.0], 0.0, 0),
+        t!(floats_right_11, &[1.0, 1.0, 1.0, 1.0], 1.0, 4),
+        t!(floats_right_12, &[1.0, 1.0, 1.0, 1.0], 2.0, 4),
+        t!(floats_right_13, &[1.0, 2.0], 0.0, 0),
+        t!(floats_right_14, &[1.0, 2.0], 1.0, 1),
+        t!(floats_right_15, &[1.0, 2.0], 2.0, 2),
+        t!(floats_right_16, &[1.0, 2.0], 3.0, 2),
+        t!(floats_right_17, &[1.0, 1.0, 2.0, 2.0], 0.0, 0),
+        t!(floats_right_18, &[1.0, 1.0, 2.0, 2.0], 1.0, 2),
+        t!(floats_right_19, &[1.0, 1.0, 2.0, 2.0], 2.0, 4),
+        t!(floats_right_20, &[1.0, 1.0, 2.0, 2.0], 3.0, 4),
+        t!(floats_right_21, &[1.0, 2.0, 3.0], 0.0, 0),
+        t!(floats_right_22, &[1.0, 2.0, 3.0], 1.0, 1),
+        t!(floats_right_23, &[1.0, 2.0, 3.0], 2.0, 2),
+        t!(floats_right_24, &[1.0, 2.0, 3.0], 3.0, 3),
+        t!(floats_right_25, &[1.0, 2.0, 3.0], 4.0, 3),
+        t!(floats_right_26, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 0.0, 0),
+        t!(floats_right_27, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 1.0, 1),
+        t!(floats_right_28, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 2.0, 3),
+        t!(floats_right_29, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 3.0, 6),
+        t!(floats_right_30, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 4.0, 10),
+        t!(floats_right_31, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 5.0, 10),
+    ];
+
+    const LEFT_FLOAT_CASES: TestCollection<f32> = &[
+        t!(floats_left_0, &[], 1.0, 0),
+        t!(floats_left_1, &[1.0], 0.0, 0),
+        t!(floats_left_2, &[1.0], 1.0, 0),
+        t!(floats_left_3, &[1.0], 2.0, 1),
+        t!(floats_left_4, &[1.0, 1.0], 0.0, 0),
+        t!(floats_left_5, &[1.0, 1.0], 1.0, 0),
+        t!(floats_left_6, &[1.0, 1.0], 2.0, 2),
+        t!(floats_left_7, &[1.0, 1.0, 1.0], 0.0, 0),
+        t!(floats_left_8, &[1.0, 1.0, 1.0], 1.0, 0),
+        t!(floats_left_9, &[1.0, 1.0, 1.0], 2.0, 3),
+        t!(floats_left_10, &[1.0, 1.0, 1.0, 1.0], 0.0, 0),
+        t!(floats_left_11, &[1.0, 1.0, 1.0, 1.0], 1.0, 0),
+        t!(floats_left_12, &[1.0, 1.0, 1.0, 1.0], 2.0, 4),
+        t!(floats_left_13, &[1.0, 2.0], 0.0, 0),
+        t!(floats_left_14, &[1.0, 2.0], 1.0, 0),
+        t!(floats_left_15, &[1.0, 2.0], 2.0, 1),
+        t!(floats_left_16, &[1.0, 2.0], 3.0, 2),
+        t!(floats_left_17, &[1.0, 1.0, 2.0, 2.0], 0.0, 0),
+        t!(floats_left_18, &[1.0, 1.0, 2.0, 2.0], 1.0, 0),
+        t!(floats_left_19, &[1.0, 1.0, 2.0, 2.0], 2.0, 2),
+        t!(floats_left_20, &[1.0, 1.0, 2.0, 2.0], 3.0, 4),
+        t!(floats_left_21, &[1.0, 2.0, 3.0], 0.0, 0),
+        t!(floats_left_22, &[1.0, 2.0, 3.0], 1.0, 0),
+        t!(floats_left_23, &[1.0, 2.0, 3.0], 2.0, 1),
+        t!(floats_left_24, &[1.0, 2.0, 3.0], 3.0, 2),
+        t!(floats_left_25, &[1.0, 2.0, 3.0], 4.0, 3),
+        t!(floats_left_26, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 0.0, 0),
+        t!(floats_left_27, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 1.0, 0),
+        t!(floats_left_28, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 2.0, 1),
+        t!(floats_left_29, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 3.0, 3),
+        t!(floats_left_30, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 4.0, 6),
+        t!(floats_left_31, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 5.0, 10),
+    ];
+
+    #[test]
+    fn bisect_right_precomputed() {
+        run_bisect_tests(TestDirection::Right, RIGHT_INT_CASES);
+        run_bisect_tests(TestDirection::Right, RIGHT_FLOAT_CASES);
+    }
+
+    #[test]
+    fn bisect_left_precomputed() {
+        run_bisect_tests(TestDirection::Left, LEFT_INT_CASES);
+        run_bisect_tests(TestDirection::Left, LEFT_FLOAT_CASES);
+    }
+
+    #[test]
+    fn bisect_right_slice_precomputed() {
+        run_bisect_slice_tests(TestDirection::Right, RIGHT_INT_CASES);
+        run_bisect_slice_tests(TestDirection::Right, RIGHT_FLOAT_CASES);
+    }
+
+    #[test]
+    fn bisect_left_slice_precomputed() {
+        run_bisect_slice_tests(TestDirection::Left, LEFT_INT_CASES);
+        run_bisect_slice_tests(TestDirection::Left, LEFT_FLOAT_CASES);
+    }
+
+    #[test]
+    #[should_panic]
+    fn right_slice_index_out_of_bounds() {
+        let a: Vec<u32> = (0..10).collect();
+        // 10 does not fit within 5..10 so the search goes into the out of bounds range
+        bisect_right_slice(&a, &10, 5..15);
+    }
+
+    #[test]
+    #[should_panic]
+    fn left_slice_index_out_of_bounds() {
+        let a: Vec<u32> = (0..10).collect();
+        // 10 does not fit within 5..10 so the search goes into the out of bounds range
+        bisect_left_slice(&a, &10, 5..15);
+    }
+
+    #[test]
+    #[should_panic]
+    fn right_slice_index_out_of_bounds_when_not_searched() {
+        let a: Vec<u32> = (0..10).collect();
+        // 5 fits within 0..10 bounds so the search *doesnt* actually index into the out of bounds
+        bisect_right_slice(&a, &5, ..15);
+    }
+
+    #[test]
+    #[should_panic]
+    fn left_slice_index_out_of_bounds_when_not_searched() {
+        let a: Vec<u32> = (0..10).collect();
+        // 5 fits within 0..10 bounds so the search *doesnt* actually index into the out of bounds
+        bisect_left_slice(&a, &5, ..15);
+    }
+
+    fn run_bisect_tests<T: Clone + PartialOrd>(direction: TestDirection, test_cases: TestCollection<T>) {
+        let bisect_func = match direction {
+            TestDirection::Left => bisect_left,
+            TestDirection::Right => bisect_right,
+        };
+
+        for test_case in test_cases {
+            let data = test_case.a.to_vec();
+            assert_eq!(
+                test_case.expected_index,
+                bisect_func(&data, &test_case.x),
+                "{}",
+                test_case.name
+            );
+        }
+    }
+
+    fn run_bisect_slice_tests<T: Clone + PartialOrd>(
+        direction: TestDirection,
+        test_cases: TestCollection<T>,
+    ) {
+        let bisect_func = match direction {
+            TestDirection::Left => bisect_left_slice,
+            TestDirection::Right => bisect_right_slice,
+        };
+
+        for test_case in test_cases {
+            let data = test_case.a.to_vec();
+            for lo in 0..4 {
+                for hi in 3..8 {
+                    let hi = std::cmp::min(data.len(), hi);
+                    let ip = bisect_func(&data, &test_case.x, lo..hi);
+
+                    match direction {
+                        TestDirection::Left => {
+                            if ip < hi {
+                                assert!(test_case.x <= data[ip]);
+                            }
+
+                            if ip > lo {
+                                assert!(data[ip - 1] < test_case.x)
+                            }
+                        }
+                        TestDirection::Right => {
+                            if ip < hi {
+                                assert!(test_case.x < data[ip]);
+                            }
+
+                            if ip > lo {
+                                assert!(data[ip - 1] <= test_case.x)
+                            }
+                        }
+                    }
+
+                    assert_eq!(
+                        ip,
+                        std::cmp::max(lo, std::cmp::min(hi, test_case.expected_index))
+                    );
+                }
+            }
+        }
+    }
+
+    #[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+    struct Person {
+        name: String,
+        age: u32,
+    }
+
+    fn arb_person() -> impl Strategy<Value = Person> {
+        ("[a-z]*", 1..100_u32).prop_map(|(name, age)| Person { name, age })
+    }
+
+    fn check_index_right_invariant<T, F>(a: &[T], target: &T, index: usize, mut f: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        // See `bisect_right_by` docs
+        assert!(a[..index]
+            .iter()
+            .all(|x| matches!(f(x, target), Ordering::Less | Ordering::Equal)));
+        assert!(a[index..].iter().all(|x| f(x, target) == Ordering::Greater));
+    }
+
+    fn check_index_left_invariant<T, F>(a: &[T], target: &T, index: usize, mut f: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        // See `bisect_left_by` docs
+        assert!(a[..index].iter().all(|x| f(x, target) == Ordering::Less));
+        assert!(a[index..]
+            .iter()
+            .all(|x| matches!(f(x, target), Ordering::Greater | Ordering::Equal)));
+    }
+
+    proptest! {
+
+        #[test]
+        fn test_bisect_left_index_invariant(
+            mut nums in prop::collection::vec(any::<u32>(), 0..500),
+            num in any::<u32>()
+        ) {
+            nums.sort();
+
+            let i = bisect_left(&nums, &num);
+
+            check_index_left_invariant(&nums, &num, i, u32::cmp);
+        }
+
+        #[test]
+        fn test_bisect_left_by_index_invariant(
+            mut people in prop::collection::vec(arb_person(), 0..500),
+            new_person in arb_person()
+        ) {
+            // Sort by age only
+            let f = |a: &Person, b: &Person| a.age.cmp(&b.age);
+
+            people.sort_by(f);
+
+            let i = bisect_left_by(&people, |p| f(p, &new_person));
+
+            check_index_left_invariant(&people, &new_person, i, f);
+
+            // Sort by name only
+            let f = |a: &Person, b: &Person| a.name.cmp(&b.name);
+
+            people.sort_by(f);
+
+            let i = bisect_left_by(&people, |p| f(p, &new_person));
+
+            check_index_left_invariant(&people, &new_person, i, f);
+        }
+
+        #[test]
+        fn test_bisect_right_index_invariant(
+            mut nums in prop::collection::vec(any::<u32>(), 0..500),
+            num in any::<u32>()
+        ) {
+            nums.sort();
+
+            let i = bisect_right(&nums, &num);
+
+            check_index_right_invariant(&nums, &num, i, u32::cmp)
+        }
+
+        #[test]
+        fn test_bisect_right_by_index_invariant(
+            mut people in prop::collection::vec(arb_person(), 0..500),
+            new_person in arb_person()
+        ) {
+            // Sort by age only
+            let f = |a: &Person, b: &Person| a.age.cmp(&b.age);
+
+            people.sort_by(f);
+
+            let i = bisect_right_by(&people, |p| f(&new_person, p));
+
+            check_index_right_invariant(&people, &new_person, i, f);
+
+
+            // Sort by name only
+            let f = |a: &Person, b: &Person| a.name.cmp(&b.name);
+
+            people.sort_by(f);
+
+            let i = bisect_right_by(&people, |p| f(&new_person, p));
+
+            check_index_right_invariant(&people, &new_person, i, f);
+        }
+
+        #[test]
+        fn test_insort_vs_vec_sort(
+            digits in prop::collection::vec(0..10, 0..500)
+        ) {
+            let left_digits = HashSet::<i32>::from_iter(vec![0, 2, 4, 6, 8]);
+            let mut insorted = vec![];
+
+            for digit in digits {
+                let f = if  left_digits.contains(&digit) {
+                    insort_left
+                } else {
+                    insort_right
+                };
+
+                f(&mut insorted, digit);
+            }
+
+            let vec_sorted = {
+                let mut v = insorted.clone();
+                v.sort();
+                v
+            };
+
+            assert_eq!(vec_sorted, insorted);
+        }
+    }
+
+    #[test]
+    fn partition_around_matches_bisect() {
+        let a = [1, 2, 2, 3, 3, 3, 4, 4, 4, 4];
+        for pivot in 0..6 {
+            let (lt_count, le_count) = partition_around(&a, &pivot);
+            assert_eq!(lt_count, bisect_left(&a, &pivot));
+            assert_eq!(le_count, bisect_right(&a, &pivot));
+            assert!(a[lt_count..le_count].iter().all(|e| *e == pivot));
+        }
+
+        assert_eq!(partition_around(&[] as &[i32], &1), (0, 0));
+    }
+
+    proptest! {
+        #[test]
+        fn test_search_recursive_vs_iterative(
+            mut nums in prop::collection::vec(any::<u32>(), 0..500),
+            num in any::<u32>()
+        ) {
+            nums.sort();
+
+            let iterative = search_iterative(0, nums.len(), |mid| nums[mid] < num);
+            let recursive = search_recursive(0, nums.len(), &mut |mid| nums[mid] < num);
+            assert_eq!(iterative, recursive);
+
+            let iterative = search_iterative(0, nums.len(), |mid| nums[mid] <= num);
+            let recursive = search_recursive(0, nums.len(), &mut |mid| nums[mid] <= num);
+            assert_eq!(iterative, recursive);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_probe_matches_bisect(
+            mut nums in prop::collection::vec(0..50_u32, 0..200),
+            num in 0..50_u32
+        ) {
+            nums.sort();
+            let present = nums.contains(&num);
+
+            let (i, found) = probe_left(&nums, |e| e.cmp(&num));
+            assert_eq!(i, bisect_left(&nums, &num));
+            assert_eq!(found, present);
+
+            let (i, found) = probe_right(&nums, |e| num.cmp(e));
+            assert_eq!(i, bisect_right(&nums, &num));
+            assert_eq!(found, present);
+        }
+    }
+
+    #[test]
+    fn runs_match_merged() {
+        let runs: &[&[i32]] = &[&[1, 3, 5, 7, 9], &[2, 3, 3, 8], &[], &[0, 5, 5, 10, 12]];
+        let mut merged: Vec<i32> = runs.iter().flat_map(|run| run.iter().copied()).collect();
+        merged.sort();
+
+        for x in -1..14 {
+            assert_eq!(bisect_left_runs(runs, &x), bisect_left(&merged, &x));
+            assert_eq!(
+                count_equal_runs(runs, &x),
+                bisect_right(&merged, &x) - bisect_left(&merged, &x)
+            );
+        }
+    }
+
+    #[test]
+    fn bisect_left_chained_two_fields() {
+        let mut people: Vec<(u32, &str)> = vec![
+            (30, "carol"),
+            (25, "bob"),
+            (30, "alice"),
+            (25, "dave"),
+            (40, "erin"),
+            (30, "frank"),
+        ];
+        people.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(b.1)));
+
+        let by_age = |e: &(u32, &str)| e.0.cmp(&30);
+        let by_name = |e: &(u32, &str)| e.1.cmp("carol");
+
+        // Only the first field
+        assert_eq!(bisect_left_chained(&people, &[&by_age]), 2);
+        assert_eq!(
+            bisect_left_chained(&people, &[&by_age]),
+            bisect_left_by(&people, by_age)
+        );
+
+        // Both fields
+        assert_eq!(bisect_left_chained(&people, &[&by_age, &by_name]), 3);
+        assert_eq!(people[3], (30, "carol"));
+
+        // Absent second field within a present first field
+        let by_name = |e: &(u32, &str)| e.1.cmp("zed");
+        assert_eq!(bisect_left_chained(&people, &[&by_age, &by_name]), 5);
+
+        assert_eq!(bisect_left_chained(&people, &[]), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insort_into_right_small_destination() {
+        let mut dst = [0; 3];
+        insort_into_right(&[1, 2, 3], 2, &mut dst);
+    }
+
+    proptest! {
+        #[test]
+        fn test_insort_into_right_vs_insort(
+            mut nums in prop::collection::vec(0..20_i32, 0..100),
+            num in 0..20_i32
+        ) {
+            nums.sort();
+            let mut dst = vec![0; nums.len() + 1];
+
+            let i = insort_into_right(&nums, num, &mut dst);
+
+            let mut expected = nums.clone();
+            insort_right(&mut expected, num);
+            assert_eq!(dst, expected);
+            assert_eq!(i, bisect_right(&nums, &num));
+        }
+    }
+
+    #[test]
+    fn bisect_left_budgeted_sufficient_and_exhausted() {
+        let a: Vec<u32> = (0..100).collect();
+
+        assert_eq!(bisect_left_budgeted(&a, &37, 7), Ok(37));
+        assert_eq!(bisect_left_budgeted(&a, &200, 64), Ok(100));
+        assert_eq!(bisect_left_budgeted(&[] as &[u32], &1, 0), Ok(0));
+
+        for budget in 0..3 {
+            assert!(
+                bisect_left_budgeted(&a, &37, budget).is_err(),
+                "budget of {} should not be enough",
+                budget
+            );
+        }
+        assert_eq!(bisect_left_budgeted(&a, &37, 0), Err(0));
+
+        // Each probe at least halves the bracket, so after `budget` probes it is `lo..=hi` with
+        // `hi <= lo + (len >> budget)`, and the true answer must lie inside it
+        let dups: Vec<u32> = (0..100).map(|i| i / 3).collect();
+        for a in [&a, &dups] {
+            for x in 0..=101 {
+                let expected = bisect_left(a, &x);
+                for budget in 0..9 {
+                    match bisect_left_budgeted(a, &x, budget) {
+                        Ok(i) => assert_eq!(i, expected),
+                        Err(lo) => {
+                            let hi = lo + (a.len() >> budget);
+                            assert!(
+                                lo <= expected && expected <= hi,
+                                "{} not in {}..={} for {} with budget {}",
+                                expected,
+                                lo,
+                                hi,
+                                x,
+                                budget
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bisect_left_entry_present_and_absent() {
+        let a = [1, 3, 3, 5];
+
+        assert_eq!(bisect_left_entry(&a, &3), Some((1, &3)));
+        assert_eq!(bisect_left_entry(&a, &1), Some((0, &1)));
+        // Absent, but the insertion point is in range
+        assert_eq!(bisect_left_entry(&a, &4), None);
+        // Absent, insertion point is past the end
+        assert_eq!(bisect_left_entry(&a, &6), None);
+        assert_eq!(bisect_left_entry(&[] as &[i32], &6), None);
+
+        let people = [(1, "a"), (3, "b"), (3, "c"), (5, "d")];
+        assert_eq!(
+            bisect_left_entry_by(&people, |p| p.0.cmp(&3)),
+            Some((1, &(3, "b")))
+        );
+        assert_eq!(bisect_left_entry_by(&people, |p| p.0.cmp(&4)), None);
+        assert_eq!(bisect_left_entry_by(&people, |p| p.0.cmp(&6)), None);
+    }
+
+    #[test]
+    fn bisect_get_entries() {
+        let mut entries = vec![(1, "one"), (3, "three"), (5, "five")];
+
+        assert_eq!(bisect_get(&entries, &3), Some(&"three"));
+        assert_eq!(bisect_get(&entries, &1), Some(&"one"));
+        assert_eq!(bisect_get(&entries, &4), None);
+        assert_eq!(bisect_get(&entries, &6), None);
+
+        *bisect_get_mut(&mut entries, &5).unwrap() = "FIVE";
+        assert_eq!(entries[2], (5, "FIVE"));
+        assert_eq!(bisect_get_mut(&mut entries, &0), None);
+    }
+
+    #[test]
+    fn bisect_cyclic_angles() {
+        let a = [300.0, 320.0, 350.0, 10.0, 20.0, 40.0];
+
+        // Either side of the 0/360 boundary lands between 350 and 10
+        assert_eq!(bisect_cyclic(&a, &355.0, 360.0), 3);
+        assert_eq!(bisect_cyclic(&a, &5.0, 360.0), 3);
+        assert_eq!(bisect_cyclic(&a, &365.0, 360.0), 3);
+        assert_eq!(bisect_cyclic(&a, &-5.0, 360.0), 3);
+
+        assert_eq!(bisect_cyclic(&a, &310.0, 360.0), 1);
+        assert_eq!(bisect_cyclic(&a, &15.0, 360.0), 4);
+        assert_eq!(bisect_cyclic(&a, &20.0, 360.0), 5);
+        assert_eq!(bisect_cyclic(&a, &100.0, 360.0), 6);
+        assert_eq!(bisect_cyclic(&a, &300.0, 360.0), 1);
+
+        // An unrotated slice behaves like `bisect_right`, with wrapped values going at the end
+        let b = [10, 20, 30];
+        assert_eq!(bisect_cyclic(&b, &25, 360), 2);
+        assert_eq!(bisect_cyclic(&b, &5, 360), 3);
+        assert_eq!(bisect_cyclic(&[] as &[i32], &5, 360), 0);
+    }
+
+    #[test]
+    fn bisect_cyclic_unsigned_does_not_overflow() {
+        // `100 + 200` would overflow a `u8`
+        let a: [u8; 4] = [150, 190, 20, 60];
+        assert_eq!(bisect_cyclic(&a, &100, 200), 4);
+        assert_eq!(bisect_cyclic(&a, &170, 200), 1);
+        assert_eq!(bisect_cyclic(&a, &250, 200), 3);
+        assert_eq!(bisect_cyclic(&[10_u8, 20], &255, 200), 2);
+    }
+
+    #[test]
+    fn bisect_cyclic_last_equal_to_first() {
+        // The final 5 comes after the wrap point
+        let a = [5, 7, 5];
+        assert_eq!(bisect_cyclic(&a, &3, 10), 2);
+        assert_eq!(bisect_cyclic(&a, &6, 10), 1);
+        assert_eq!(bisect_cyclic(&a, &8, 10), 2);
+        let b = [5, 5, 8, 2, 5, 5];
+        assert_eq!(bisect_cyclic(&b, &3, 10), 4);
+        assert_eq!(bisect_cyclic(&b, &1, 10), 3);
+        assert_eq!(bisect_cyclic(&b, &6, 10), 2);
+        // A constant slice has no wrap point
+        assert_eq!(bisect_cyclic(&[5, 5, 5], &5, 10), 3);
+        assert_eq!(bisect_cyclic(&[5, 5, 5], &3, 10), 3);
+    }
+
+    #[test]
+    fn insort_right_undoable_restores() {
+        let mut a = vec![(1, 'a'), (2, 'a'), (2, 'b'), (3, 'a')];
+        let before = a.clone();
+
+        let token = insort_right_undoable(&mut a, (2, 'a'));
+        assert_eq!(token.index(), 2);
+        assert_eq!(a.len(), before.len() + 1);
+        assert_eq!(token.undo(&mut a), (2, 'a'));
+        assert_eq!(a, before);
+
+        let mut a = vec![1, 2, 2, 2, 3];
+        let before = a.clone();
+        let first = insort_right_undoable(&mut a, 2);
+        let second = insort_right_undoable(&mut a, 0);
+        assert_eq!(second.undo(&mut a), 0);
+        assert_eq!(first.undo(&mut a), 2);
+        assert_eq!(a, before);
+    }
+
+    #[test]
+    fn bisect_dyn_runtime_comparators() {
+        type Order = Box<dyn Fn(&i32, &i32) -> Ordering>;
+
+        let orders: Vec<Order> = vec![
+            Box::new(|a, b| a.cmp(b)),
+            Box::new(|a, b| b.cmp(a)),
+            Box::new(|a, b| a.abs().cmp(&b.abs())),
+        ];
+        let data = [-5, 3, -2, 8, 0, 3, -7];
+        let target = 3;
+
+        for order in &orders {
+            let mut a = data.to_vec();
+            a.sort_by(|x, y| order(x, y));
+
+            let left = bisect_left_dyn(&a, &|e| order(e, &target));
+            assert_eq!(left, bisect_left_by(&a, |e| order(e, &target)));
+            assert!(a[..left]
+                .iter()
+                .all(|e| order(e, &target) == Ordering::Less));
+
+            let right = bisect_right_dyn(&a, &|e| order(&target, e));
+            assert_eq!(right, bisect_right_by(&a, |e| order(&target, e)));
+            assert!(a[right..]
+                .iter()
+                .all(|e| order(e, &target) == Ordering::Greater));
+        }
+    }
+
+    #[test]
+    fn bisect_deref_references() {
+        let values = [5, 1, 3, 3, 9];
+        let mut a: Vec<&i32> = values.iter().collect();
+        a.sort();
+
+        assert_eq!(bisect_left_deref(&a, &3), 1);
+        assert_eq!(bisect_right_deref(&a, &3), 3);
+        assert_eq!(bisect_left_deref(&a, &0), 0);
+        assert_eq!(bisect_right_deref(&a, &10), 5);
+
+        let new = [3, 0];
+        insort_left_deref(&mut a, &new[0]);
+        assert!(std::ptr::eq(a[1], &new[0]));
+        insort_right_deref(&mut a, &new[1]);
+        assert!(std::ptr::eq(a[0], &new[1]));
+        assert_eq!(a, vec![&0, &1, &3, &3, &3, &5, &9]);
+
+        let mut words: Vec<&str> = vec!["apple", "cherry"];
+        insort_right_deref(&mut words, "banana");
+        assert_eq!(words, vec!["apple", "banana", "cherry"]);
+    }
+
+    #[test]
+    fn running_median_known_sequence() {
+        let mut m = RunningMedian::new();
+        assert_eq!(m.median(), None);
+        assert_eq!(m.median_averaged(), None);
+
+        let pushes = [5, 15, 1, 3, 8, 7, 9, 10];
+        let lower = [5, 5, 5, 3, 5, 5, 7, 7];
+        let averaged = [5, 10, 5, 4, 5, 6, 7, 7];
+
+        for ((x, l), avg) in pushes.iter().zip(&lower).zip(&averaged) {
+            m.push(*x);
+            assert_eq!(m.median(), Some(*l));
+            assert_eq!(m.median_averaged(), Some(*avg));
+        }
+        assert_eq!(m.len(), pushes.len());
+        assert_eq!(m.as_slice(), &[1, 3, 5, 7, 8, 9, 10, 15]);
+    }
+
+    #[test]
+    fn running_median_averaged_near_type_limits() {
+        let mut m = RunningMedian::new();
+        m.push(200u8);
+        m.push(100u8);
+        assert_eq!(m.median_averaged(), Some(150));
+        m.push(u8::MAX);
+        m.push(u8::MAX);
+        assert_eq!(m.median_averaged(), Some(227));
+
+        let mut m = RunningMedian::new();
+        m.push(i16::MIN);
+        m.push(i16::MAX);
+        assert_eq!(m.median_averaged(), Some(-1));
+        m.push(-4i16);
+        m.push(-3i16);
+        assert_eq!(m.median_averaged(), Some(-4));
+    }
+
+    #[test]
+    fn bisect_valley_arms() {
+        let a = [9, 7, 4, 2, 1, 3, 4, 8, 10];
+
+        assert_eq!(bisect_valley(&a, &4), (Some(2), Some(6)));
+        assert_eq!(bisect_valley(&a, &7), (Some(1), None));
+        assert_eq!(bisect_valley(&a, &10), (None, Some(8)));
+        assert_eq!(bisect_valley(&a, &1), (Some(4), Some(4)));
+        assert_eq!(bisect_valley(&a, &5), (None, None));
+        assert_eq!(bisect_valley(&a, &0), (None, None));
+        assert_eq!(bisect_valley(&a, &11), (None, None));
+
+        // Degenerate valleys with only one arm
+        assert_eq!(bisect_valley(&[1, 2, 3], &2), (None, Some(1)));
+        assert_eq!(bisect_valley(&[3, 2, 1], &2), (Some(1), None));
+        assert_eq!(bisect_valley(&[] as &[i32], &2), (None, None));
+    }
+
+    #[test]
+    fn insort_balanced_spreads_duplicates() {
+        let mut a = vec![1, 2, 2, 2, 2, 3];
+        let mut rotor = 0;
+        let mut offsets = vec![];
+
+        for _ in 0..12 {
+            let lo = bisect_left(&a, &2);
+            let hi = bisect_right(&a, &2);
+            let i = insort_balanced(&mut a, 2, &mut rotor);
+            assert!(lo <= i && i <= hi);
+            offsets.push(i - lo);
+            assert!(a.windows(2).all(|w| w[0] <= w[1]));
+        }
+        assert_eq!(rotor, 12);
+        assert_eq!(offsets[..4], [0, 1, 2, 3]);
+        let distinct: HashSet<usize> = offsets.iter().copied().collect();
+        assert!(distinct.len() > 2);
+
+        // A value with no equal run always has a single position
+        let mut b = vec![1, 3];
+        assert_eq!(insort_balanced(&mut b, 2, &mut rotor), 1);
+        assert_eq!(b, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn bisect_fractional_positions() {
+        let a = [0.0, 1.0, 2.0, 4.0, 8.0];
+
+        for (i, x) in a.iter().enumerate() {
+            assert_eq!(bisect_fractional(&a, *x), i as f64);
+        }
+        assert_eq!(bisect_fractional(&a, 0.5), 0.5);
+        assert_eq!(bisect_fractional(&a, 3.0), 2.5);
+        assert_eq!(bisect_fractional(&a, 6.0), 3.5);
+        assert_eq!(bisect_fractional(&a, 7.0), 3.75);
+
+        assert_eq!(bisect_fractional(&a, -1.0), 0.0);
+        assert_eq!(bisect_fractional(&a, 9.0), 4.0);
+        assert_eq!(bisect_fractional(&[], 9.0), 0.0);
+        assert_eq!(bisect_fractional(&[1.0, 2.0, 2.0, 3.0], 2.0), 2.0);
+        // Duplicates of the first element also give the rightmost of them
+        assert_eq!(bisect_fractional(&[1.0, 1.0, 2.0], 1.0), 1.0);
+        assert_eq!(bisect_fractional(&[1.0, 1.0, 2.0], 1.5), 1.5);
+        assert!(bisect_fractional(&[1.0, 2.0], f64::NAN).is_nan());
+        assert!(bisect_fractional(&[1.0], f64::NAN).is_nan());
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn bisect_left_trace_midpoints() {
+        let a = [1, 2, 3, 4, 5, 6, 7];
+        let (i, trace) = bisect_left_trace(&a, &6);
+
+        assert_eq!(i, bisect_left(&a, &6));
+        assert_eq!(
+            trace,
+            "lo=0 mid=3 hi=7 a[mid]=4 < x -> right\n\
+             lo=4 mid=5 hi=7 a[mid]=6 >= x -> left\n\
+             lo=4 mid=4 hi=5 a[mid]=5 < x -> right\n"
+        );
+
+        assert_eq!(bisect_left_trace(&[] as &[i32], &6), (0, String::new()));
+    }
+
+    #[test]
+    fn bisect_left_with_mid_strategies() {
+        let a = [1, 2, 2, 3, 3, 3, 4, 4, 4, 4];
+        let standard = |lo: usize, hi: usize| lo + (hi - lo) / 2;
+        let right_biased = |lo: usize, hi: usize| lo + (hi - lo) * 3 / 4;
+        let linear = |lo: usize, _hi: usize| lo;
+
+        for x in 0..6 {
+            let expected = bisect_left(&a, &x);
+            assert_eq!(bisect_left_with_mid(&a, &x, standard), expected);
+            assert_eq!(bisect_left_with_mid(&a, &x, right_biased), expected);
+            assert_eq!(bisect_left_with_mid(&a, &x, linear), expected);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn bisect_left_with_mid_out_of_range() {
+        bisect_left_with_mid(&[1, 2, 3], &2, |_lo, hi| hi);
+    }
+
+    #[test]
+    fn bisect_left_mapped_log_space() {
+        let a = [1, 2, 4, 8, 16, 32];
+        let ln = |t: &i32| (*t as f64).ln();
+
+        assert_eq!(bisect_left_mapped(&a, &8f64.ln(), ln), 3);
+        assert_eq!(bisect_left_mapped(&a, &10f64.ln(), ln), 4);
+        assert_eq!(bisect_left_mapped(&a, &0.0, ln), 0);
+        assert_eq!(bisect_left_mapped(&a, &100f64.ln(), ln), 6);
+
+        let mapped: Vec<f64> = a.iter().map(ln).collect();
+        for x in [-1.0, 0.5, 1.0, 2.0, 3.3, 5.0] {
+            assert_eq!(bisect_left_mapped(&a, &x, ln), bisect_left(&mapped, &x));
+        }
+    }
+
+    #[test]
+    fn try_bisect_left_f64_nan() {
+        let a = [1.0, 2.0, 2.0, 3.0];
+        assert_eq!(try_bisect_left_f64(&a, 2.0), Ok(1));
+        assert_eq!(try_bisect_left_f64(&a, 4.0), Ok(4));
+        assert_eq!(try_bisect_left_f64(&a, f64::NAN), Err(FloatError::Nan));
+        assert_eq!(try_bisect_left_f64(&[], f64::NAN), Err(FloatError::Nan));
+
+        // The first probe is the middle element
+        let b = [1.0, 2.0, f64::NAN, 3.0, 4.0];
+        assert_eq!(try_bisect_left_f64(&b, 3.5), Err(FloatError::Nan));
+        assert_eq!(
+            FloatError::Nan.to_string(),
+            "NaN encountered in float comparison"
+        );
+    }
+
+    #[test]
+    fn bisect_asref_containers() {
+        use std::borrow::Cow;
+        use std::sync::Arc;
+
+        let v = vec![1, 2, 2, 3, 5];
+        let x = 2;
+        let (left, right) = (bisect_left(&v, &x), bisect_right(&v, &x));
+
+        let borrowed: Cow<[i32]> = Cow::Borrowed(&v);
+        let owned: Cow<[i32]> = Cow::Owned(v.clone());
+        let arc: Arc<[i32]> = v.clone().into();
+        let boxed: Box<[i32]> = v.clone().into_boxed_slice();
+
+        assert_eq!(bisect_left_asref(&v[..], &x), left);
+        assert_eq!(bisect_left_asref(&v, &x), left);
+        assert_eq!(bisect_left_asref(&borrowed, &x), left);
+        assert_eq!(bisect_left_asref(&owned, &x), left);
+        assert_eq!(bisect_left_asref(&arc, &x), left);
+        assert_eq!(bisect_left_asref(&boxed, &x), left);
+
+        assert_eq!(bisect_right_asref(v.clone(), &x), right);
+        assert_eq!(bisect_right_asref(borrowed, &x), right);
+        assert_eq!(bisect_right_asref(owned, &x), right);
+        assert_eq!(bisect_right_asref(arc, &x), right);
+        assert_eq!(bisect_right_asref(boxed, &x), right);
+    }
+
+    #[test]
+    fn insort_windowed_right_evicts_relative_to_newest() {
+        let mut a = vec![];
+
+        assert!(insort_windowed_right(&mut a, 100, 'a', 10).is_empty());
+        assert!(insort_windowed_right(&mut a, 95, 'b', 10).is_empty());
+        assert!(insort_windowed_right(&mut a, 105, 'c', 10).is_empty());
+        // Out of order, still within the window of the newest (105)
+        assert!(insort_windowed_right(&mut a, 96, 'd', 10).is_empty());
+        assert_eq!(a, vec![(95, 'b'), (96, 'd'), (100, 'a'), (105, 'c')]);
+
+        // Out of order and already too old: evicted immediately
+        assert_eq!(insort_windowed_right(&mut a, 90, 'e', 10), vec![(90, 'e')]);
+
+        // A new maximum moves the window forward
+        assert_eq!(
+            insort_windowed_right(&mut a, 110, 'f', 10),
+            vec![(95, 'b'), (96, 'd')]
+        );
+        assert_eq!(a, vec![(100, 'a'), (105, 'c'), (110, 'f')]);
+
+        // Equal timestamps go after existing ones
+        insort_windowed_right(&mut a, 105, 'g', 10);
+        assert_eq!(a[1..3], [(105, 'c'), (105, 'g')]);
+    }
+
+    #[test]
+    fn find_run_matches() {
+        let a = [1, 2, 2, 2, 3, 5];
+
+        assert_eq!(find_run(&a, &1), Some((0, &a[0..1])));
+        assert_eq!(find_run(&a, &2), Some((1, &[2, 2, 2][..])));
+        assert_eq!(find_run(&a, &5), Some((5, &[5][..])));
+        assert_eq!(find_run(&a, &4), None);
+        assert_eq!(find_run(&a, &0), None);
+        assert_eq!(find_run(&a, &6), None);
+
+        let rows = [(1, 'a'), (2, 'b'), (2, 'c'), (4, 'd')];
+        assert_eq!(
+            find_run_by(&rows, |r| r.0.cmp(&2)),
+            Some((1, &[(2, 'b'), (2, 'c')][..]))
+        );
+        assert_eq!(find_run_by(&rows, |r| r.0.cmp(&3)), None);
+    }
+
+    proptest! {
+        #[test]
+        fn test_equal_range_vs_bisect(
+            mut nums in prop::collection::vec(0..20_u32, 0..200),
+            num in 0..20_u32
+        ) {
+            nums.sort();
+            let expected = bisect_left(&nums, &num)..bisect_right(&nums, &num);
+
+            assert_eq!(equal_range(&nums, &num), expected.clone());
+            assert_eq!(equal_range_by(&nums, |e| e.cmp(&num)), expected);
+
+            let floats: Vec<f64> = nums.iter().map(|&n| f64::from(n)).collect();
+            let nan = f64::NAN;
+            assert_eq!(
+                equal_range(&floats, &nan),
+                bisect_left(&floats, &nan)..bisect_right(&floats, &nan)
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_dedup_sorted_counted_vs_btreemap(
+            mut nums in prop::collection::vec(0..10_u32, 0..300)
+        ) {
+            nums.sort();
+            let mut reference = std::collections::BTreeMap::new();
+            for n in &nums {
+                *reference.entry(*n).or_insert(0) += 1;
+            }
+
+            let counts = dedup_sorted_counted(&mut nums);
+
+            assert_eq!(nums, reference.keys().copied().collect::<Vec<_>>());
+            assert_eq!(counts, reference.values().copied().collect::<Vec<usize>>());
+        }
+    }
+
+    #[test]
+    fn bisect_left_with_perm_maps_rows() {
+        let column = [30, 10, 20, 10, 50];
+        let mut perm: Vec<usize> = (0..column.len()).collect();
+        perm.sort_by_key(|&i| column[i]);
+        let keys: Vec<i32> = perm.iter().map(|&i| column[i]).collect();
+
+        assert_eq!(bisect_left_with_perm(&keys, &perm, &30), Some(0));
+        assert_eq!(bisect_left_with_perm(&keys, &perm, &50), Some(4));
+        assert_eq!(bisect_left_with_perm(&keys, &perm, &20), Some(2));
+        // The leftmost of the duplicates, which a stable argsort keeps in row order
+        assert_eq!(bisect_left_with_perm(&keys, &perm, &10), Some(1));
+        assert_eq!(bisect_left_with_perm(&keys, &perm, &40), None);
+        assert_eq!(bisect_left_with_perm(&keys, &perm, &60), None);
+    }
+
+    #[test]
+    fn insort_by_without_ord() {
+        let mut a: Vec<f64> = vec![];
+        for x in [3.0, -1.0, 2.5, f64::INFINITY, 0.0, -0.0] {
+            insort_left_by(&mut a, x, f64::total_cmp);
+        }
+        for x in [1.5, f64::NEG_INFINITY] {
+            insort_right_by(&mut a, x, f64::total_cmp);
+        }
+
+        let mut expected = a.clone();
+        expected.sort_by(f64::total_cmp);
+        assert_eq!(a, expected);
+        assert_eq!(a.len(), 8);
+    }
+
+    #[test]
+    fn bisect_any_by_exits_early() {
+        let a: Vec<u32> = (0..1023).collect();
+
+        let mut comparisons = 0;
+        let i = bisect_any_by(&a, |e| {
+            comparisons += 1;
+            e.cmp(&511)
+        });
+        assert_eq!(i, Some(511));
+        assert_eq!(comparisons, 1);
+
+        let mut comparisons = 0;
+        bisect_left_by(&a, |e| {
+            comparisons += 1;
+            e.cmp(&511)
+        });
+        assert_eq!(comparisons, 10);
+
+        let b = [1, 2, 2, 2, 3];
+        let i = bisect_any_by(&b, |e| e.cmp(&2)).unwrap();
+        assert_eq!(b[i], 2);
+        assert_eq!(bisect_any_by(&b, |e| e.cmp(&4)), None);
+        assert_eq!(bisect_any_by(&[] as &[u32], |e| e.cmp(&4)), None);
+    }
+
+    #[test]
+    fn tolerance_range_bands() {
+        let a = [0.0, 0.5, 1.0, 1.0, 1.5, 2.0, 3.0];
+
+        assert_eq!(tolerance_range(&a, 1.0, 0.5), 1..5);
+        assert_eq!(tolerance_range(&a, 1.0, 0.0), 2..4);
+        assert_eq!(tolerance_range(&a, 1.0, 0.0), equal_range(&a, &1.0));
+        assert_eq!(tolerance_range(&a, 2.5, 0.1), 6..6);
+        // Straddling either end
+        assert_eq!(tolerance_range(&a, -1.0, 1.5), 0..2);
+        assert_eq!(tolerance_range(&a, 3.5, 1.0), 6..7);
+        // Entirely outside the data
+        assert_eq!(tolerance_range(&a, -5.0, 1.0), 0..0);
+        assert_eq!(tolerance_range(&a, 10.0, 1.0), 7..7);
+    }
+
+    #[test]
+    fn insort_right_fixed_to_capacity() {
+        let mut buf = [0; 4];
+        let mut len = 0;
+
+        assert_eq!(insort_right_fixed(&mut buf, &mut len, 5), Ok(0));
+        assert_eq!(insort_right_fixed(&mut buf, &mut len, 1), Ok(0));
+        assert_eq!(insort_right_fixed(&mut buf, &mut len, 5), Ok(2));
+        assert_eq!(insort_right_fixed(&mut buf, &mut len, 3), Ok(1));
+        assert_eq!(len, 4);
+        assert_eq!(buf, [1, 3, 5, 5]);
+
+        assert_eq!(insort_right_fixed(&mut buf, &mut len, 2), Err(2));
+        assert_eq!(len, 4);
+        assert_eq!(buf, [1, 3, 5, 5]);
+
+        let mut empty: [i32; 0] = [];
+        assert_eq!(insort_right_fixed(&mut empty, &mut 0, 1), Err(1));
+    }
+
+    #[test]
+    fn mode_longest_run() {
+        assert_eq!(mode(&[1, 2, 2, 3, 3, 3, 4, 4]), Some((3, 3)));
+        assert_eq!(mode(&[1, 1, 2, 2]), Some((0, 2)));
+        assert_eq!(mode(&[1, 2, 3, 4]), Some((0, 1)));
+        assert_eq!(mode(&[7; 100]), Some((0, 100)));
+        assert_eq!(mode(&[] as &[i32]), None);
+    }
+
+    #[test]
+    fn bracket_neighbours() {
+        let a = [0.0, 1.0, 2.0, 2.0, 4.0];
+
+        assert_eq!(bracket(&a, &0.5), Some((0, 1)));
+        assert_eq!(bracket(&a, &3.0), Some((3, 4)));
+        // On the grid
+        assert_eq!(bracket(&a, &0.0), Some((0, 1)));
+        assert_eq!(bracket(&a, &1.0), Some((1, 2)));
+        assert_eq!(bracket(&a, &2.0), Some((3, 4)));
+        assert_eq!(bracket(&a, &4.0), Some((3, 4)));
+        // Out of range
+        assert_eq!(bracket(&a, &-0.5), None);
+        assert_eq!(bracket(&a, &4.5), None);
+        assert_eq!(bracket(&[1.0], &1.0), None);
+        // Unordered
+        assert_eq!(bracket(&a, &f64::NAN), None);
+
+        for x in [0.0, 0.5, 1.5, 2.0, 3.9, 4.0] {
+            let (i, j) = bracket(&a, &x).unwrap();
+            assert!(a[i] <= x && x <= a[j]);
+        }
+    }
+
+    #[test]
+    fn distinct_iter_first_occurences() {
+        let a = [1, 1, 1, 2, 3, 3, 5, 5, 5, 5, 5, 5, 5, 5, 5, 9];
+        let expected: Vec<(usize, &i32)> = a
+            .iter()
+            .enumerate()
+            .filter(|(i, x)| *i == 0 || a[i - 1] != **x)
+            .collect();
+
+        assert_eq!(distinct_iter(&a).collect::<Vec<_>>(), expected);
+        assert_eq!(
+            distinct_iter(&a).collect::<Vec<_>>(),
+            vec![(0, &1), (3, &2), (4, &3), (6, &5), (15, &9)]
+        );
+        assert_eq!(distinct_iter(&[] as &[i32]).count(), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_gallop_vs_search(
+            mut nums in prop::collection::vec(0..50_u32, 0..200),
+            num in 0..50_u32,
+            start in 0..200_usize
+        ) {
+            nums.sort();
+            let start = std::cmp::min(start, bisect_left(&nums, &num));
+
+            assert_eq!(
+                gallop(start, nums.len(), |mid| nums[mid] < num),
+                search(start, nums.len(), |mid| nums[mid] < num)
+            );
+        }
+    }
+
+    #[test]
+    fn insort_callback_vs_insort_right() {
+        let values = [5, 3, 8, 3, 1, 9, 5, 0];
+        let mut expected = vec![];
+        let mut backing = std::cell::RefCell::new(vec![]);
+
+        for x in values.iter().copied() {
+            insort_right(&mut expected, x);
+
+            let len = backing.get_mut().len();
+            let i = insort_callback(
+                x,
+                len,
+                |i| x.cmp(&backing.borrow()[i]),
+                |i, x| backing.borrow_mut().insert(i, x),
+            );
+            assert_eq!(backing.borrow()[i], x);
+        }
+        assert_eq!(backing.into_inner(), expected);
+    }
+
+    #[test]
+    fn sorted_vec_by_uses_stored_comparator() {
+        // Descending by age, the reverse of the natural order
+        let mut people = SortedVecBy::new(|a: &(u32, &str), b: &(u32, &str)| b.0.cmp(&a.0));
+
+        assert_eq!(people.insert((30, "carol")), 0);
+        assert_eq!(people.insert((40, "alice")), 0);
+        assert_eq!(people.insert((20, "bob")), 2);
+        // Equal under the comparator, goes after the existing 30
+        assert_eq!(people.insert((30, "dave")), 2);
+        assert_eq!(
+            people.as_slice(),
+            &[(40, "alice"), (30, "carol"), (30, "dave"), (20, "bob")]
+        );
+
+        assert!(people.contains(&(30, "anyone")));
+        assert!(!people.contains(&(35, "carol")));
+        assert_eq!(people.find(&(30, "")), Some(1));
+
+        assert_eq!(
+            people.range(&(35, ""), &(20, "")),
+            &[(30, "carol"), (30, "dave")]
+        );
+        assert!(people.range(&(20, ""), &(35, "")).is_empty());
+
+        assert_eq!(people.first_ge(&(35, "")), Some(&(30, "carol")));
+        assert_eq!(people.first_gt(&(30, "")), Some(&(20, "bob")));
+        assert_eq!(people.first_ge(&(10, "")), None);
+
+        assert_eq!(people.remove(&(30, "")), Some((30, "carol")));
+        assert_eq!(people.len(), 3);
+
+        let v = SortedVecBy::from_vec(vec![1, 5, 3], |a: &i32, b: &i32| b.cmp(a));
+        assert_eq!(v.into_vec(), vec![5, 3, 1]);
+    }
+
+    #[test]
+    fn bisect_state_steps() {
+        let a: Vec<u32> = (0..100).map(|i| i * 2).collect();
+
+        for target in [0, 1, 51, 100, 198, 250] {
+            let mut state = BisectState::start(a.len());
+            let mut probes = 0;
+            let mut step = state.step();
+            let index = loop {
+                match step {
+                    BisectStep::Probe(i) => {
+                        assert_eq!(state.probe_index(), Some(i));
+                        probes += 1;
+                        step = state.feed(a[i].cmp(&target));
+                    }
+                    BisectStep::Done(i) => break i,
+                }
+            };
+            assert_eq!(index, bisect_left(&a, &target));
+            assert!(probes <= 7);
+            assert_eq!(state.probe_index(), None);
+        }
+
+        // A known comparison sequence over 0..8
+        let mut state = BisectState::start(8);
+        assert_eq!(state.step(), BisectStep::Probe(4));
+        assert_eq!(state.feed(Ordering::Less), BisectStep::Probe(6));
+        assert_eq!(state.feed(Ordering::Greater), BisectStep::Probe(5));
+        assert_eq!(state.feed(Ordering::Equal), BisectStep::Done(5));
+
+        assert_eq!(BisectState::start(0).step(), BisectStep::Done(0));
+    }
+
+    #[test]
+    fn bisect_state_huge_len() {
+        let mut state = BisectState::start(usize::MAX);
+        assert_eq!(state.step(), BisectStep::Probe(usize::MAX / 2));
+        let mut step = state.feed(Ordering::Less);
+        let mut probes = 1;
+        while let BisectStep::Probe(_) = step {
+            step = state.feed(Ordering::Less);
+            probes += 1;
+        }
+        assert_eq!(step, BisectStep::Done(usize::MAX));
+        assert!(probes <= usize::BITS);
+    }
+
+    #[test]
+    fn count_in_range_intervals() {
+        let a = [1, 2, 2, 3, 4, 4, 4, 5];
+
+        assert_eq!(count_in_range(&a, &2, &4), 3);
+        assert_eq!(count_in_range_inclusive(&a, &2, &4), 6);
+        assert_eq!(count_in_range_exclusive(&a, &2, &4), 1);
+        assert_eq!(count_in_range_left_open(&a, &2, &4), 4);
+
+        // Whole array
+        assert_eq!(count_in_range(&a, &0, &10), a.len());
+        assert_eq!(count_in_range_inclusive(&a, &1, &5), a.len());
+        // Straddling the ends
+        assert_eq!(count_in_range(&a, &0, &2), 1);
+        assert_eq!(count_in_range_inclusive(&a, &4, &9), 4);
+        // Empty and inverted
+        assert_eq!(count_in_range(&a, &3, &3), 0);
+        assert_eq!(count_in_range_inclusive(&a, &3, &3), 1);
+        assert_eq!(count_in_range(&a, &4, &2), 0);
+        assert_eq!(count_in_range_inclusive(&a, &4, &2), 0);
+        assert_eq!(count_in_range_exclusive(&a, &4, &2), 0);
+        assert_eq!(count_in_range_left_open(&a, &4, &2), 0);
+        assert_eq!(count_in_range(&a, &6, &9), 0);
+    }
+
+    #[test]
+    fn bisect_left_segmented_three_segments() {
+        let a = [5, 7, 9, 1, 2, 7, 7, 3, 4, 8];
+        let segments = [0..3, 3..7, 7..10];
+
+        assert_eq!(bisect_left_segmented(&a, &segments, &7), vec![1, 5, 9]);
+        assert_eq!(bisect_left_segmented(&a, &segments, &0), vec![0, 3, 7]);
+        assert_eq!(bisect_left_segmented(&a, &segments, &10), vec![3, 7, 10]);
+        assert!(bisect_left_segmented(&a, &[], &10).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bisect_left_segmented_overlapping() {
+        bisect_left_segmented(&[1, 2, 3, 4], &[0..3, 2..4], &2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bisect_left_segmented_out_of_bounds() {
+        bisect_left_segmented(&[1, 2, 3, 4], &[0..2, 2..5], &2);
+    }
+
+    #[test]
+    fn between_windows() {
+        let a = [1, 2, 2, 3, 4, 4, 4, 5];
+
+        for low in 0..7 {
+            for high in 0..7 {
+                let half_open = between(&a, &low, &high);
+                assert!(half_open.iter().all(|e| low <= *e && *e < high));
+                assert_eq!(half_open.len(), count_in_range(&a, &low, &high));
+
+                let closed = between_incl(&a, &low, &high);
+                assert!(closed.iter().all(|e| low <= *e && *e <= high));
+                assert_eq!(closed.len(), count_in_range_inclusive(&a, &low, &high));
+            }
+        }
+
+        assert_eq!(between(&a, &2, &4), &[2, 2, 3]);
+        assert_eq!(between_incl(&a, &2, &4), &[2, 2, 3, 4, 4, 4]);
+        assert!(between(&a, &4, &2).is_empty());
+        assert!(between_incl(&a, &8, &9).is_empty());
+    }
+
+    #[test]
+    fn merge_join_matches() {
+        let left = [
+            (1, 'a'),
+            (2, 'b'),
+            (3, 'c'),
+            (3, 'd'),
+            (5, 'e'),
+            (6, 'f'),
+            (6, 'g'),
+        ];
+        let right = [
+            (0, "x"),
+            (1, "one"),
+            (3, "three"),
+            (4, "four"),
+            (6, "p"),
+            (6, "q"),
+        ];
+
+        assert_eq!(
+            merge_join(&left, &right),
+            vec![
+                // 1:1
+                (1, &'a', &"one"),
+                // many:1
+                (3, &'c', &"three"),
+                (3, &'d', &"three"),
+                // many:many
+                (6, &'f', &"p"),
+                (6, &'f', &"q"),
+                (6, &'g', &"p"),
+                (6, &'g', &"q"),
+            ]
+        );
+
+        // 1:many
+        let left = [(7, 1)];
+        let right = [(7, 'a'), (7, 'b'), (8, 'c')];
+        assert_eq!(
+            merge_join(&left, &right),
+            vec![(7, &1, &'a'), (7, &1, &'b')]
+        );
+
+        let empty: [(i32, char); 0] = [];
+        assert!(merge_join(&left, &empty).is_empty());
+    }
+
+    #[test]
+    fn insertion_point_accessors() {
+        let a = [10, 20, 20, 30];
+
+        let start = locate_left_typed(&a, &5);
+        assert_eq!(start.as_index(), 0);
+        assert_eq!(start.predecessor(), None);
+        assert_eq!(start.successor(a.len()), Some(0));
+
+        let middle = locate_right_typed(&a, &20);
+        assert_eq!(middle.as_index(), 3);
+        assert_eq!(middle.predecessor(), Some(2));
+        assert_eq!(middle.successor(a.len()), Some(3));
+
+        let end = locate_right_typed(&a, &30);
+        assert_eq!(end.as_index(), 4);
+        assert_eq!(end.predecessor(), Some(3));
+        assert_eq!(end.successor(a.len()), None);
+        assert_eq!(end.clamp_to(2), 2);
+        assert_eq!(end.clamp_to(10), 4);
+
+        assert!(locate_left_typed(&a, &20) < locate_right_typed(&a, &20));
+        assert_eq!(locate_left_typed(&[] as &[i32], &1).successor(0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "bound index overflow")]
+    fn excluded_start_bound_overflow() {
+        bisect_left_slice(&[1, 2, 3], &2, (Excluded(usize::MAX), Unbounded));
+    }
+
+    #[test]
+    #[should_panic(expected = "bound index overflow")]
+    fn included_end_bound_overflow() {
+        bisect_right_slice(&[1, 2, 3], &2, (Unbounded, Included(usize::MAX)));
+    }
+
+    #[test]
+    fn bisect_left_field_secondary_indices() {
+        let table = [
+            ("carol", 31),
+            ("alice", 25),
+            ("erin", 40),
+            ("bob", 31),
+            ("dave", 19),
+        ];
+
+        let mut by_name: Vec<usize> = (0..table.len()).collect();
+        by_name.sort_by_key(|&i| table[i].0);
+        let mut by_age: Vec<usize> = (0..table.len()).collect();
+        by_age.sort_by_key(|&i| table[i].1);
+
+        let name = |row: &(&'static str, u32)| row.0;
+        let age = |row: &(&str, u32)| row.1;
+
+        let position = bisect_left_field(&table, &by_name, name, &"dave").unwrap();
+        assert_eq!(table[by_name[position]], ("dave", 19));
+        assert_eq!(bisect_left_field(&table, &by_name, name, &"frank"), None);
+
+        let position = bisect_left_field(&table, &by_age, age, &31).unwrap();
+        assert_eq!(position, 2);
+        assert_eq!(table[by_age[position]].1, 31);
+        assert_eq!(table[by_age[position + 1]].1, 31);
+        assert_eq!(bisect_left_field(&table, &by_age, age, &30), None);
+        assert_eq!(bisect_left_field(&table, &by_age, age, &50), None);
+    }
+
+    #[test]
+    fn bisect_left_verbose_partitions() {
+        let a = [1, 2, 2, 3, 5, 5, 8];
+
+        for x in 0..10 {
+            let (i, left, right) = bisect_left_verbose(&a, &x);
+            assert_eq!(i, bisect_left(&a, &x));
+            assert_eq!(left + right, a.len());
+            assert_eq!(left, a.iter().filter(|e| **e < x).count());
+            assert_eq!(right, a.iter().filter(|e| **e >= x).count());
+        }
+        assert_eq!(bisect_left_verbose(&[] as &[i32], &1), (0, 0, 0));
+    }
+
+    #[test]
+    fn bisect_ring_wraps() {
+        let ring = [100_u64, 200, 300];
+
+        assert_eq!(bisect_ring(&ring, &0), 0);
+        assert_eq!(bisect_ring(&ring, &100), 0);
+        assert_eq!(bisect_ring(&ring, &101), 1);
+        assert_eq!(bisect_ring(&ring, &300), 2);
+        assert_eq!(bisect_ring(&ring, &301), 0);
+        assert_eq!(bisect_ring(&ring, &u64::MAX), 0);
+        assert_eq!(bisect_ring(&[5], &9), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bisect_ring_empty() {
+        bisect_ring(&[] as &[u64], &1);
+    }
+
+    #[test]
+    fn insort_index_right_over_unsorted_data() {
+        let mut data = vec![];
+        let mut index = vec![];
+
+        for x in [50, 10, 40, 10, 30, 20, 50, 0] {
+            data.push(x);
+            insort_index_right(&data, &mut index, data.len() - 1);
+        }
+
+        assert_eq!(data, vec![50, 10, 40, 10, 30, 20, 50, 0]);
+        let traversed: Vec<i32> = index.iter().map(|&i| data[i]).collect();
+        assert_eq!(traversed, vec![0, 10, 10, 20, 30, 40, 50, 50]);
+        // Equal values keep their insertion order
+        assert_eq!(index[1..3], [1, 3]);
+        assert_eq!(index[6..8], [0, 6]);
+    }
+
+    proptest! {
+        #[test]
+        fn test_bisect_right_sorted_targets_vs_bisect_right(
+            mut nums in prop::collection::vec(0..100_u32, 0..200),
+            mut targets in prop::collection::vec(0..110_u32, 0..100)
+        ) {
+            nums.sort();
+            targets.sort();
+
+            let expected: Vec<usize> = targets.iter().map(|t| bisect_right(&nums, t)).collect();
+            assert_eq!(bisect_right_sorted_targets(&nums, &targets), expected);
+        }
+    }
+
+    #[test]
+    fn find_range_matches() {
+        let a = [1, 2, 2, 2, 3, 5];
+
+        assert_eq!(find_range(&a, &1), Some(0..1));
+        assert_eq!(find_range(&a, &5), Some(5..6));
+        assert_eq!(find_range(&a, &2), Some(1..4));
+        assert_eq!(find_range(&a, &0), None);
+        assert_eq!(find_range(&a, &4), None);
+        assert_eq!(find_range(&a, &6), None);
+        assert_eq!(find_range(&[] as &[i32], &1), None);
+
+        let rows = [(1, 'a'), (2, 'b'), (2, 'c'), (4, 'd')];
+        assert_eq!(find_range_by(&rows, |r| r.0.cmp(&2)), Some(1..3));
+        assert_eq!(find_range_by(&rows, |r| r.0.cmp(&3)), None);
+    }
+
+    #[test]
+    fn bisect_left_variant_matches() {
+        #[derive(Debug, PartialEq, PartialOrd)]
+        enum Shape {
+            Point,
+            Circle(u32),
+            Rect(u32, u32),
+        }
+
+        fn order(s: &Shape) -> usize {
+            match s {
+                Shape::Point => 0,
+                Shape::Circle(_) => 1,
+                Shape::Rect(..) => 2,
+            }
+        }
+
+        let a = [
+            Shape::Point,
+            Shape::Point,
+            Shape::Circle(1),
+            Shape::Circle(4),
+            Shape::Rect(2, 3),
+        ];
+        assert_eq!(bisect_left_variant(&a, order, 0), 0);
+        assert_eq!(bisect_left_variant(&a, order, 1), 2);
+        assert_eq!(bisect_left_variant(&a, order, 2), 4);
+        assert_eq!(bisect_left_variant(&a, order, 3), 5);
+
+        // A missing variant gives the position of the next one
+        let b = [Shape::Point, Shape::Rect(1, 1)];
+        assert_eq!(bisect_left_variant(&b, order, 1), 1);
+    }
+
+    proptest! {
+        #[test]
+        fn test_gallop_back_vs_search(
+            mut nums in prop::collection::vec(0..50_u32, 0..200),
+            num in 0..50_u32,
+            end in 0..200_usize
+        ) {
+            nums.sort();
+            let end = std::cmp::max(std::cmp::min(end, nums.len()), bisect_left(&nums, &num));
+
+            assert_eq!(
+                gallop_back(0, end, |mid| nums[mid] < num),
+                search(0, end, |mid| nums[mid] < num)
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_cursor_vs_bisect_left(
+            mut nums in prop::collection::vec(0..50_u32, 0..100),
+            ops in prop::collection::vec((0..55_u32, any::<bool>()), 0..100)
+        ) {
+            nums.sort();
+            let mut cursor = Cursor::default();
+
+            for (x, insert) in ops {
+                if insert {
+                    let before = cursor.index();
+                    let mut expected = nums.clone();
+                    insort_left(&mut expected, x);
+
+                    let index = cursor.insort(&mut nums, x);
+                    assert_eq!(nums, expected);
+                    assert_eq!(index, bisect_left(&nums, &x));
+                    let shifted = if index <= before { before + 1 } else { before };
+                    assert_eq!(cursor.index(), shifted);
+                } else {
+                    assert_eq!(cursor.search_near(&nums, &x), bisect_left(&nums, &x));
+                    assert_eq!(cursor.index(), bisect_left(&nums, &x));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cursor_follows_caret() {
+        let mut a: Vec<u32> = (0..20).map(|i| i * 10).collect();
+        let mut caret = Cursor::new(5);
+
+        // Typing moves the caret forward a little at a time
+        for x in &[55, 61, 72, 75] {
+            let expected = bisect_left(&a, x);
+            assert_eq!(caret.search_near(&a, x), expected);
+            let index = caret.insort(&mut a, *x);
+            assert_eq!(index, expected);
+            assert_eq!(caret.index(), expected + 1);
+        }
+
+        // And then jumps back
+        assert_eq!(caret.search_near(&a, &15), 2);
+        caret.inserted_at(0);
+        assert_eq!(caret.index(), 3);
+        caret.inserted_at(4);
+        assert_eq!(caret.index(), 3);
+    }
+
+    #[test]
+    fn insort_right_with_prev_matches() {
+        let mut a = vec![10, 20, 30];
+
+        assert_eq!(insort_right_with_prev(&mut a, 5), (0, None));
+        assert_eq!(insort_right_with_prev(&mut a, 25), (3, Some(20)));
+        assert_eq!(insort_right_with_prev(&mut a, 20), (3, Some(20)));
+        assert_eq!(insort_right_with_prev(&mut a, 40), (6, Some(30)));
+        assert_eq!(a, vec![5, 10, 20, 20, 25, 30, 40]);
+
+        let mut empty = Vec::new();
+        assert_eq!(insort_right_with_prev(&mut empty, 1), (0, None));
+    }
+
+    #[test]
+    fn bisect_left_abs_matches() {
+        let a = [0.0, -1.0, 2.0, -3.0];
+
+        assert_eq!(bisect_left_abs(&a, 0.0), 0);
+        assert_eq!(bisect_left_abs(&a, 0.5), 1);
+        assert_eq!(bisect_left_abs(&a, -0.5), 1);
+        assert_eq!(bisect_left_abs(&a, 1.0), 1);
+        assert_eq!(bisect_left_abs(&a, -2.0), 2);
+        assert_eq!(bisect_left_abs(&a, 2.5), 3);
+        assert_eq!(bisect_left_abs(&a, -4.0), 4);
+        assert_eq!(bisect_left_abs(&[], 1.0), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_bounds_of_vs_bisect(
+            mut nums in prop::collection::vec(0..10_u32, 0..200),
+            num in 0..12_u32
+        ) {
+            nums.sort();
+
+            let (left, right) = bounds_of(&nums, &num);
+            assert!(left <= right);
+            assert_eq!((left, right), (bisect_left(&nums, &num), bisect_right(&nums, &num)));
+
+            let floats: Vec<f64> = nums.iter().map(|&n| f64::from(n)).collect();
+            let nan = f64::NAN;
+            assert_eq!(
+                bounds_of(&floats, &nan),
+                (bisect_left(&floats, &nan), bisect_right(&floats, &nan))
+            );
+        }
+    }
+
+    #[test]
+    fn bisect_left_slice_policy_on_empty_range() {
+        let a = [1, 2, 3, 4, 5, 6, 7];
+
+        assert_eq!(
+            bisect_left_slice_policy(&a, &3, 5..5, EmptyRangePolicy::ReturnStart),
+            Some(5)
+        );
+        assert_eq!(
+            bisect_left_slice_policy(&a, &3, 5..5, EmptyRangePolicy::ReturnNone),
+            None
+        );
+
+        // Non-empty ranges are unaffected by the policy
+        for policy in &[
+            EmptyRangePolicy::ReturnStart,
+            EmptyRangePolicy::ReturnNone,
+            EmptyRangePolicy::Panic,
+        ] {
+            assert_eq!(
+                bisect_left_slice_policy(&a, &3, 1..5, *policy),
+                Some(bisect_left_slice(&a, &3, 1..5))
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "empty search range")]
+    fn bisect_left_slice_policy_panics_on_empty_range() {
+        bisect_left_slice_policy(&[1, 2, 3, 4, 5, 6, 7], &3, 5..5, EmptyRangePolicy::Panic);
+    }
+
+    #[test]
+    fn stab_matches() {
+        let intervals = [(0, 5), (5, 8), (10, 20)];
+
+        // Inside an interval
+        assert_eq!(stab(&intervals, &0), Some(0));
+        assert_eq!(stab(&intervals, &4), Some(0));
+        assert_eq!(stab(&intervals, &5), Some(1));
+        assert_eq!(stab(&intervals, &15), Some(2));
+        assert_eq!(stab(&intervals, &19), Some(2));
+
+        // In a gap
+        assert_eq!(stab(&intervals, &8), None);
+        assert_eq!(stab(&intervals, &9), None);
+
+        // Outside the whole range
+        assert_eq!(stab(&intervals, &-1), None);
+        assert_eq!(stab(&intervals, &20), None);
+        assert_eq!(stab(&[] as &[(i32, i32)], &0), None);
+    }
+
+    #[test]
+    fn locate_clamped_matches() {
+        let a = [10, 20, 30, 40, 50, 60];
+
+        // Searching a[1..5] = [20, 30, 40, 50]
+        assert_eq!(locate_clamped(&a, &5, 1..5), Clamp::Below(1));
+        assert_eq!(locate_clamped(&a, &20, 1..5), Clamp::Below(1));
+        assert_eq!(locate_clamped(&a, &21, 1..5), Clamp::Within(2));
+        assert_eq!(locate_clamped(&a, &50, 1..5), Clamp::Within(4));
+        assert_eq!(locate_clamped(&a, &51, 1..5), Clamp::Above(5));
+        assert_eq!(locate_clamped(&a, &100, 1..5), Clamp::Above(5));
+
+        assert_eq!(locate_clamped(&a, &100, ..), Clamp::Above(6));
+        assert_eq!(locate_clamped(&a, &35, 3..3), Clamp::Below(3));
+        assert_eq!(locate_clamped(&a, &35, ..).index(), bisect_left(&a, &35));
+    }
+
+    #[test]
+    fn ord_f64_sorts_nan_last() {
+        let mut a: Vec<OrdF64> = [3.0, f64::NAN, -1.0, 0.0, -0.0, -f64::NAN, f64::INFINITY]
+            .iter()
+            .map(|&x| OrdF64::from(x))
+            .collect();
+        a.sort();
+
+        let sorted: Vec<f64> = a.iter().map(|&x| x.into()).collect();
+        assert_eq!(sorted[..5], [-1.0, -0.0, 0.0, 3.0, f64::INFINITY]);
+        assert!(sorted[0..5].iter().all(|x| !x.is_nan()));
+        assert!(sorted[5..].iter().all(|x| x.is_nan()));
+        assert!(sorted[1].is_sign_negative() && sorted[2].is_sign_positive());
+
+        assert_eq!(bisect_left(&a, &OrdF64(0.0)), 2);
+        assert_eq!(bisect_right(&a, &OrdF64(-0.0)), 2);
+        assert_eq!(bisect_left(&a, &OrdF64(f64::NAN)), 5);
+        assert_eq!(bisect_right(&a, &OrdF64(f64::NAN)), 7);
+        assert_eq!(bisect_right(&a, &OrdF64(f64::INFINITY)), 5);
+
+        insort_right(&mut a, OrdF64(2.0));
+        assert_eq!(a[3], OrdF64(2.0));
+    }
+
+    #[test]
+    fn bisect_at_matches() {
+        let a = [1, 3, 3, 5];
+
+        assert_eq!(bisect_right_at(&a, &0), (0, Some(&1)));
+        assert_eq!(bisect_right_at(&a, &3), (3, Some(&5)));
+        assert_eq!(bisect_right_at(&a, &5), (4, None));
+        assert_eq!(bisect_left_at(&a, &0), (0, Some(&1)));
+        assert_eq!(bisect_left_at(&a, &3), (1, Some(&3)));
+        assert_eq!(bisect_left_at(&a, &6), (4, None));
+
+        for x in 0..7 {
+            let (i, e) = bisect_right_at(&a, &x);
+            assert_eq!(e, a.get(i));
+            let (i, e) = bisect_left_at(&a, &x);
+            assert_eq!(e, a.get(i));
+        }
+    }
+
+    #[test]
+    fn insort_stable_keeps_insertion_order() {
+        let mut back = Vec::new();
+        let mut front = Vec::new();
+        let mut expected = Vec::new();
+        for tag in 0..60_u32 {
+            let key = tag * 7 % 5;
+            insort_stable(&mut back, (key, tag), |e| e.0.cmp(&key));
+            insort_stable_front(&mut front, (key, tag), |e| e.0.cmp(&key));
+            expected.push((key, tag));
+        }
+
+        expected.sort_by_key(|e| e.0);
+        assert_eq!(back, expected);
+
+        expected.sort_by(|x, y| x.0.cmp(&y.0).then(y.1.cmp(&x.1)));
+        assert_eq!(front, expected);
+    }
+
+    #[test]
+    fn bisect_left_seek_matches() {
+        let keys: Vec<u32> = vec![3, 8, 8, 15, 42, 100];
+        let mut bytes = Vec::new();
+        for k in &keys {
+            // An 8-byte record: a big-endian key followed by a payload
+            bytes.extend_from_slice(&k.to_be_bytes());
+            bytes.extend_from_slice(b"data");
+        }
+        let mut file = std::io::Cursor::new(bytes);
+        let by_key = |record: &[u8], target: &[u8]| record[..4].cmp(target);
+
+        for target in 0..110_u32 {
+            let index =
+                bisect_left_seek(&mut file, keys.len(), 8, &target.to_be_bytes(), by_key).unwrap();
+            assert_eq!(index, bisect_left(&keys, &target));
+        }
+
+        // Claiming more records than there are fails rather than reading garbage
+        let result = bisect_left_seek(&mut file, 20, 8, &200_u32.to_be_bytes(), by_key);
+        assert_eq!(
+            result.unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn around_matches() {
+        let a = [0, 10, 20, 30, 40, 50, 60, 70];
+
+        // In the middle, with the extra element of an odd window on the right
+        assert_eq!(around(&a, &35, 4), 2..6);
+        assert_eq!(around(&a, &35, 3), 3..6);
+        assert_eq!(around(&a, &30, 2), 2..4);
+        assert_eq!(around(&a, &35, 0), 4..4);
+
+        // Near either end the window shifts to keep k elements
+        assert_eq!(around(&a, &5, 4), 0..4);
+        assert_eq!(around(&a, &-5, 3), 0..3);
+        assert_eq!(around(&a, &65, 4), 4..8);
+        assert_eq!(around(&a, &100, 3), 5..8);
+
+        // k larger than the slice
+        assert_eq!(around(&a, &35, 20), 0..8);
+        assert_eq!(around(&[] as &[i32], &1, 3), 0..0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_counts_partition(
+            mut nums in prop::collection::vec(0..10_u32, 0..100),
+            num in 0..12_u32
+        ) {
+            nums.sort();
+
+            let lt = nums.iter().filter(|&&e| e < num).count();
+            let eq = nums.iter().filter(|&&e| e == num).count();
+            assert_eq!(count_lt(&nums, &num), lt);
+            assert_eq!(count_equal(&nums, &num), eq);
+            assert_eq!(count_le(&nums, &num), lt + eq);
+            assert_eq!(
+                count_lt(&nums, &num) + count_equal(&nums, &num) + count_gt(&nums, &num),
+                nums.len()
+            );
+            assert_eq!(count_ge(&nums, &num) + count_lt(&nums, &num), nums.len());
+            assert_eq!(count_le(&nums, &num) + count_gt(&nums, &num), nums.len());
+        }
+    }
+
+    #[test]
+    fn bisect_cmp_target_matches() {
+        let a = [(1, 'a'), (2, 'b'), (2, 'c'), (2, 'd'), (4, 'e')];
+
+        for target in 0..6 {
+            let cmp = |e: &(i32, char)| e.0.cmp(&target);
+            let keys: Vec<i32> = a.iter().map(|e| e.0).collect();
+            assert_eq!(bisect_left_cmp_target(&a, cmp), bisect_left(&keys, &target));
+            assert_eq!(
+                bisect_right_cmp_target(&a, cmp),
+                bisect_right(&keys, &target)
+            );
+        }
+
+        let cmp = |e: &(i32, char)| e.0.cmp(&2);
+        assert_eq!(
+            bisect_left_cmp_target(&a, cmp)..bisect_right_cmp_target(&a, cmp),
+            1..4
+        );
+    }
+
+    #[test]
+    fn bisect_matrix_matches() {
+        let m = [
+            [1, 4, 7, 11],
+            [2, 5, 8, 12],
+            [3, 6, 9, 16],
+            [10, 13, 14, 17],
+        ];
+        let at = |r: usize, c: usize| m[r][c];
+
+        for (r, row) in m.iter().enumerate() {
+            for (c, e) in row.iter().enumerate() {
+                assert_eq!(bisect_matrix(4, 4, at, e), Some((r, c)));
+            }
+        }
+        for x in &[0, 15, 18, 100] {
+            assert_eq!(bisect_matrix(4, 4, at, x), None);
+        }
+
+        // Over flat row-major storage, with a non-square shape
+        let flat = [1, 3, 5, 2, 4, 6];
+        assert_eq!(
+            bisect_matrix(2, 3, |r, c| flat[r * 3 + c], &4),
+            Some((1, 1))
+        );
+        assert_eq!(bisect_matrix(0, 3, |r, c| flat[r * 3 + c], &4), None);
+    }
+
+    #[test]
+    fn insort_right_run_info_matches() {
+        let mut a = vec![1, 3, 5, 5, 5, 5, 7];
+
+        // No existing run
+        assert_eq!(
+            insort_right_run_info(&mut a, 4),
+            RunInfo {
+                index: 2,
+                extends_run: false,
+                run_len_after: 1
+            }
+        );
+        // A singleton run
+        assert_eq!(
+            insort_right_run_info(&mut a, 7),
+            RunInfo {
+                index: 8,
+                extends_run: true,
+                run_len_after: 2
+            }
+        );
+        // A long run
+        assert_eq!(
+            insort_right_run_info(&mut a, 5),
+            RunInfo {
+                index: 7,
+                extends_run: true,
+                run_len_after: 5
+            }
+        );
+        assert_eq!(a, vec![1, 3, 4, 5, 5, 5, 5, 5, 7, 7]);
+
+        let mut empty = Vec::new();
+        assert_eq!(
+            insort_right_run_info(&mut empty, 1),
+            RunInfo {
+                index: 0,
+                extends_run: false,
+                run_len_after: 1
+            }
+        );
+    }
+
+    #[test]
+    fn bisect_left_within_values_matches() {
+        let a = [1, 3, 3, 5, 8, 13, 21];
+
+        // A window covering everything is a full bisect
+        for x in 0..25 {
+            assert_eq!(
+                bisect_left_within_values(&a, &x, &0, &100),
+                bisect_left(&a, &x)
+            );
+        }
+
+        // Narrower windows agree inside the band and clamp outside it
+        assert_eq!(bisect_left_within_values(&a, &4, &3, &8), 3);
+        assert_eq!(bisect_left_within_values(&a, &3, &3, &8), 1);
+        assert_eq!(bisect_left_within_values(&a, &8, &3, &8), 4);
+        assert_eq!(bisect_left_within_values(&a, &1, &3, &8), 1);
+        assert_eq!(bisect_left_within_values(&a, &20, &3, &8), 5);
+    }
+
+    #[test]
+    fn bisect_indexable_over_slices() {
+        let a: &[u32] = &[1, 2, 2, 4];
+        for x in 0..6 {
+            assert_eq!(bisect_left_indexable(&a, &&x), bisect_left(a, &x));
+            assert_eq!(bisect_right_indexable(&a, &&x), bisect_right(a, &x));
+        }
+    }
+
+    #[test]
+    fn bisect_indexable_huge_len_does_not_overflow() {
+        // The identity sequence 0, 1, ..., usize::MAX - 1, larger than any real allocation
+        struct Identity;
+
+        impl Indexable for Identity {
+            type Item = usize;
+
+            fn len(&self) -> usize {
+                usize::MAX
+            }
+
+            fn get(&self, i: usize) -> usize {
+                assert!(i < self.len());
+                i
+            }
+        }
+
+        for &x in &[0, 1, usize::MAX / 2, usize::MAX - 2, usize::MAX - 1] {
+            assert_eq!(bisect_left_indexable(&Identity, &x), x);
+            assert_eq!(bisect_right_indexable(&Identity, &x), x + 1);
+        }
+        assert_eq!(bisect_left_indexable(&Identity, &usize::MAX), usize::MAX);
+        assert_eq!(bisect_right_indexable(&Identity, &usize::MAX), usize::MAX);
+    }
+
+    #[test]
+    fn try_insort_right_respects_max_len() {
+        let mut a = vec![1, 3, 5];
+
+        assert_eq!(try_insort_right(&mut a, 3, 5), Ok(2));
+        assert_eq!(try_insort_right(&mut a, 0, 5), Ok(0));
+        assert_eq!(a, vec![0, 1, 3, 3, 5]);
+
+        // At capacity
+        assert_eq!(try_insort_right(&mut a, 4, 5), Err(4));
+        assert_eq!(a, vec![0, 1, 3, 3, 5]);
+        assert_eq!(try_insort_right(&mut a, 4, 2), Err(4));
+        assert_eq!(a, vec![0, 1, 3, 3, 5]);
+    }
+
+    proptest! {
+        #[test]
+        fn test_merge_iter_vs_merge_sorted(
+            mut a in prop::collection::vec(0..20_u32, 0..50),
+            mut b in prop::collection::vec(0..20_u32, 0..50)
+        ) {
+            a.sort();
+            b.sort();
+
+            let merged: Vec<u32> = merge_iter(&a, &b).cloned().collect();
+            assert_eq!(merged, merge_sorted(&a, &b));
+
+            let mut expected = [a, b].concat();
+            expected.sort();
+            assert_eq!(merged, expected);
+        }
+    }
+
+    #[test]
+    fn merge_iter_yields_references_into_inputs() {
+        let a = [1, 3];
+        let b = [1, 2, 3];
+
+        // Equal elements are only told apart by where they live, with `a`'s first
+        let merged: Vec<&i32> = merge_iter(&a, &b).collect();
+        assert_eq!(merged, [&1, &1, &2, &3, &3]);
+        assert!(std::ptr::eq(merged[0], &a[0]));
+        assert!(std::ptr::eq(merged[1], &b[0]));
+        assert!(std::ptr::eq(merged[2], &b[1]));
+        assert!(std::ptr::eq(merged[3], &a[1]));
+        assert!(std::ptr::eq(merged[4], &b[2]));
+    }
+
+    #[test]
+    fn bisect_answer_finds_minimum_capacity() {
+        // The smallest per-day capacity that ships every package within 5 days
+        let packages = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let days_needed = |capacity: i64| {
+            let (mut days, mut load) = (1, 0);
+            for &p in &packages {
+                if load + p > capacity {
+                    days += 1;
+                    load = 0;
+                }
+                load += p;
+            }
+            days
+        };
+        assert_eq!(bisect_answer(10, 55, |c| days_needed(c) <= 5), Some(15));
+
+        // All true and all false
+        assert_eq!(bisect_answer(3, 9, |_| true), Some(3));
+        assert_eq!(bisect_answer(3, 9, |_| false), None);
+        assert_eq!(bisect_answer(9, 3, |_| true), None);
+
+        // The extremes of the range
+        assert_eq!(bisect_answer(i64::MIN, i64::MAX, |x| x >= 0), Some(0));
+        assert_eq!(
+            bisect_answer(i64::MIN, i64::MAX, |x| x == i64::MAX),
+            Some(i64::MAX)
+        );
+        assert_eq!(bisect_answer(i64::MIN, i64::MAX, |_| true), Some(i64::MIN));
+    }
+
+    #[test]
+    fn bisect_left_observed_records_probes() {
+        struct Probes(Vec<(usize, usize, usize, Option<Ordering>)>);
+
+        impl BisectObserver for Probes {
+            fn on_probe(&mut self, lo: usize, mid: usize, hi: usize, ord: Option<Ordering>) {
+                self.0.push((lo, mid, hi, ord));
+            }
+        }
+
+        let a = [1, 2, 3, 4, 5, 6, 7];
+        let mut probes = Probes(Vec::new());
+        assert_eq!(bisect_left_observed(&a, &5, &mut probes), 4);
+        assert_eq!(
+            probes.0,
+            vec![
+                (0, 3, 7, Some(Ordering::Less)),
+                (4, 5, 7, Some(Ordering::Greater)),
+                (4, 4, 5, Some(Ordering::Equal)),
+            ]
+        );
+
+        let mut probes = Probes(Vec::new());
+        assert_eq!(bisect_left_observed(&[] as &[i32], &5, &mut probes), 0);
+        assert!(probes.0.is_empty());
+    }
+
+    #[test]
+    fn bisect_normalized_matches() {
+        let a: Vec<u32> = (1..=100).collect();
+
+        assert_eq!(bisect_normalized(&a, &0), 0.0);
+        assert_eq!(bisect_normalized(&a, &1), 0.01);
+        assert_eq!(bisect_normalized(&a, &25), 0.25);
+        assert_eq!(bisect_normalized(&a, &50), 0.5);
+        assert_eq!(bisect_normalized(&a, &100), 1.0);
+        assert_eq!(bisect_normalized(&a, &1000), 1.0);
+        assert_eq!(bisect_normalized(&[] as &[u32], &1), 0.0);
+    }
+
+    #[test]
+    fn bisect_left_live_skips_tombstones() {
+        // Tombstones hold stale values that would mislead a plain bisection
+        let a = [1, 3, 99, 0, 7, 9];
+        let deleted = [false, false, true, true, false, false];
+
+        assert_eq!(bisect_left_live(&a, &deleted, &0), 0);
+        assert_eq!(bisect_left_live(&a, &deleted, &2), 1);
+        assert_eq!(bisect_left_live(&a, &deleted, &3), 1);
+        assert_eq!(bisect_left_live(&a, &deleted, &5), 2);
+        assert_eq!(bisect_left_live(&a, &deleted, &7), 2);
+        assert_eq!(bisect_left_live(&a, &deleted, &8), 5);
+        assert_eq!(bisect_left_live(&a, &deleted, &10), 6);
+
+        // Leading and trailing tombstones
+        let deleted = [true, false, false, true, false, true];
+        assert_eq!(bisect_left_live(&a, &deleted, &0), 0);
+        assert_eq!(bisect_left_live(&a, &deleted, &4), 2);
+        assert_eq!(bisect_left_live(&a, &deleted, &100), 5);
+
+        // Nothing live
+        assert_eq!(bisect_left_live(&a, &[true; 6], &5), 0);
+        assert_eq!(bisect_left_live(&[] as &[i32], &[], &5), 0);
+    }
+
+    #[test]
+    fn lut_lookup_matches() {
+        let keys = [0.0, 0.5, 1.0, 2.0];
+
+        // On a key
+        assert_eq!(lut_lookup(&keys, 0.0), (0, 1, 0.0));
+        assert_eq!(lut_lookup(&keys, 1.0), (2, 3, 0.0));
+        assert_eq!(lut_lookup(&keys, 2.0), (3, 3, 0.0));
+
+        // Between keys
+        assert_eq!(lut_lookup(&keys, 0.25), (0, 1, 0.5));
+        assert_eq!(lut_lookup(&keys, 1.75), (2, 3, 0.75));
+
+        // Out of range
+        assert_eq!(lut_lookup(&keys, -1.0), (0, 0, 0.0));
+        assert_eq!(lut_lookup(&keys, 3.0), (3, 3, 0.0));
+        assert_eq!(lut_lookup(&[4.0], 5.0), (0, 0, 0.0));
+
+        // NaN
+        let (i, j, t) = lut_lookup(&keys, f64::NAN);
+        assert_eq!((i, j), (0, 0));
+        assert!(t.is_nan());
+    }
+
+    #[test]
+    fn assert_unique_sorted_matches() {
+        assert_eq!(assert_unique_sorted(&[1, 2, 5, 9]), Ok(()));
+        assert_eq!(assert_unique_sorted::<i32>(&[]), Ok(()));
+        assert_eq!(assert_unique_sorted(&[1]), Ok(()));
+
+        assert_eq!(
+            assert_unique_sorted(&[1, 2, 2, 9]),
+            Err(UniqueSortedError::Duplicate(1, 2))
+        );
+        assert_eq!(
+            assert_unique_sorted(&[1, 5, 2, 2]),
+            Err(UniqueSortedError::Unsorted(1, 2))
+        );
+        assert_eq!(
+            UniqueSortedError::Duplicate(1, 2).to_string(),
+            "elements at 1 and 2 are equal"
+        );
+    }
+
+    #[test]
+    fn insort_all_right_matches() {
+        let mut a = vec![(1, 'a'), (3, 'a'), (5, 'a')];
+        insort_all_right(&mut a, vec![(4, 'b'), (3, 'b'), (0, 'b'), (3, 'b')]);
+        assert_eq!(
+            a,
+            vec![
+                (0, 'b'),
+                (1, 'a'),
+                (3, 'a'),
+                (3, 'b'),
+                (3, 'b'),
+                (4, 'b'),
+                (5, 'a')
+            ]
+        );
+
+        let mut a = vec![1, 3, 3, 5];
+        let mut expected = a.clone();
+        for &x in &[3, 0, 6, 2] {
+            insort_right(&mut expected, x);
+        }
+        insort_all_right(&mut a, vec![3, 0, 6, 2]);
+        assert_eq!(a, expected);
+    }
+
+    #[test]
+    fn insort_all_right_with_capacity_avoids_reallocation() {
+        let mut a: Vec<u32> = (0..100).map(|i| i * 2).collect();
+        insort_all_right_with_capacity(&mut a, vec![7, 3], 300);
+        let (capacity, ptr) = (a.capacity(), a.as_ptr());
+        assert!(capacity >= 400);
+
+        // Later merges fit in the reserved capacity
+        insort_all_right(&mut a, (0..100).collect());
+        insort_all_right(&mut a, (0..100).rev().collect());
+        assert_eq!(a.capacity(), capacity);
+        assert_eq!(a.as_ptr(), ptr);
+        assert_eq!(a.len(), 302);
+        assert!(a.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn range_entries_matches() {
+        let a = [1, 3, 3, 5, 8, 13];
+
+        let entries: Vec<(usize, &i32)> = range_entries(&a, &3, &8).collect();
+        assert_eq!(entries, vec![(1, &3), (2, &3), (3, &5)]);
+        assert_eq!(entries[0].0, bisect_left(&a, &3));
+        assert!(entries.windows(2).all(|w| w[1].0 == w[0].0 + 1));
+        assert!(entries.iter().all(|&(i, e)| std::ptr::eq(e, &a[i])));
+
+        assert_eq!(range_entries(&a, &0, &100).count(), a.len());
+        assert_eq!(range_entries(&a, &8, &3).count(), 0);
+        assert_eq!(range_entries(&a, &20, &30).count(), 0);
+        assert_eq!(range_entries(&a, &4, &5).count(), 0);
+    }
+
+    #[test]
+    fn bisect_auto_by_detects_direction() {
+        let by_len = |x: &&str, y: &&str| x.len().cmp(&y.len());
+        let ascending = ["a", "bb", "cc", "ddd", "eeeee"];
+        let descending = ["eeeee", "ddd", "cc", "bb", "a"];
+
+        assert_eq!(bisect_auto_by(&ascending, &"xx", by_len), 1);
+        assert_eq!(bisect_auto_by(&descending, &"xx", by_len), 2);
+        assert_eq!(bisect_auto_by(&ascending, &"xxxx", by_len), 4);
+        assert_eq!(bisect_auto_by(&descending, &"xxxx", by_len), 1);
+        assert_eq!(bisect_auto_by(&ascending, &"", by_len), 0);
+        assert_eq!(bisect_auto_by(&descending, &"", by_len), 5);
+
+        // All equal, and empty
+        assert_eq!(bisect_auto_by(&["aa", "bb"], &"cc", by_len), 0);
+        assert_eq!(bisect_auto_by(&["aa", "bb"], &"c", by_len), 0);
+        assert_eq!(bisect_auto_by(&["aa", "bb"], &"ddd", by_len), 2);
+        assert_eq!(bisect_auto_by(&[] as &[&str], &"c", by_len), 0);
+    }
+
+    #[test]
+    fn equal_range_clamped_matches() {
+        let a = [1, 2, 3, 3, 3, 3, 4, 5, 6, 7];
+
+        // equal_range(a, 3) is 2..6
+        assert_eq!(equal_range_clamped(&a, &3, 0..10), 2..6);
+        assert_eq!(equal_range_clamped(&a, &3, 0..4), 2..4);
+        assert_eq!(equal_range_clamped(&a, &3, 4..8), 4..6);
+        assert_eq!(equal_range_clamped(&a, &3, 3..5), 3..5);
+
+        // No overlap
+        assert_eq!(equal_range_clamped(&a, &3, 7..10), 7..7);
+        assert_eq!(equal_range_clamped(&a, &3, 0..2), 2..2);
+        assert_eq!(equal_range_clamped(&a, &8, 0..5), 5..5);
+    }
+
+    /// Counts allocations made by the current thread, so tests running in parallel don't see
+    /// each other's.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    }
+
+    unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+            ALLOCATIONS.with(|n| n.set(n.get() + 1));
+            std::alloc::System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+            std::alloc::System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations_during<F: FnOnce()>(f: F) -> usize {
+        let before = ALLOCATIONS.with(|n| n.get());
+        f();
+        ALLOCATIONS.with(|n| n.get()) - before
+    }
+
+    #[test]
+    fn equal_range_family_does_not_allocate() {
+        for &len in &[0, 1, 10, 1_000, 100_000] {
+            let a: Vec<u32> = (0..len).map(|i| i / 7).collect();
+            for x in &[0, len / 14, len / 7, len] {
+                let allocations = allocations_during(|| {
+                    std::hint::black_box(equal_range(&a, x));
+                    std::hint::black_box(count_equal(&a, x));
+                    std::hint::black_box(bounds_of(&a, x));
+                });
+                assert_eq!(allocations, 0, "len {} x {}", len, x);
+            }
+        }
+
+        // The counter itself works
+        assert!(allocations_during(|| drop(std::hint::black_box(vec![1_u32]))) > 0);
+    }
+
+    #[test]
+    fn bisect_left_lazy_matches() {
+        assert_eq!(
+            bisect_left_lazy(&[] as &[u32], || panic!("target built for an empty slice")),
+            0
+        );
+
+        let a = [1, 3, 3, 5];
+        let mut calls = 0;
+        let i = bisect_left_lazy(&a, || {
+            calls += 1;
+            3
+        });
+        assert_eq!((i, calls), (1, 1));
+        assert_eq!(bisect_left_lazy(&a, || 6), 4);
+    }
+
+    #[test]
+    fn resort_position_restores_order() {
+        let sorted = vec![1, 3, 3, 5, 7, 9];
+        let cases = [
+            // (index, new value, expected position)
+            (4, 2, 1),
+            (4, 3, 3),
+            (1, 8, 4),
+            (1, 20, 5),
+            (0, 0, 0),
+            (2, 3, 2),
+            (5, 6, 4),
+        ];
+
+        for &(i, value, expected) in &cases {
+            let mut a = sorted.clone();
+            a[i] = value;
+            let j = resort_position(&a, i);
+            assert_eq!(j, expected, "a[{}] = {}", i, value);
+
+            if j < i {
+                a[j..=i].rotate_right(1);
+            } else {
+                a[i..=j].rotate_left(1);
+            }
+            assert!(a.windows(2).all(|w| w[0] <= w[1]), "{:?}", a);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_resort_move_restores_order(
+            mut nums in prop::collection::vec(0..50_u32, 1..100),
+            index in 0..100_usize,
+            value in 0..55_u32
+        ) {
+            nums.sort();
+            let i = index % nums.len();
+            nums[i] = value;
+
+            let mut expected = nums.clone();
+            expected.sort();
+
+            let j = resort_move(&mut nums, i);
+            assert_eq!(nums, expected);
+            assert_eq!(nums[j], value);
+        }
+    }
+
+    #[test]
+    fn bisect_left_het_matches() {
+        struct Record {
+            id: u32,
+            _name: &'static str,
+        }
+
+        impl Comparison<u32> for Record {
+            fn compare(&self, id: &u32) -> Ordering {
+                self.id.cmp(id)
+            }
+        }
+
+        let records = [
+            Record { id: 2, _name: "b" },
+            Record { id: 4, _name: "d" },
+            Record { id: 4, _name: "e" },
+            Record { id: 9, _name: "i" },
+        ];
+        assert_eq!(bisect_left_het(&records, &0), 0);
+        assert_eq!(bisect_left_het(&records, &4), 1);
+        assert_eq!(bisect_left_het(&records, &5), 3);
+        assert_eq!(bisect_left_het(&records, &10), 4);
+
+        // Ord types compare against themselves
+        assert_eq!(bisect_left_het(&[1, 3, 3, 5], &3), 1);
+    }
+
+    #[test]
+    fn bisect_left_checked_at_matches() {
+        let a = [1, 3, 3, 5];
+
+        assert_eq!(bisect_left_checked_at(&a, &3), (1, Some(Ordering::Equal)));
+        assert_eq!(bisect_left_checked_at(&a, &1), (0, Some(Ordering::Equal)));
+        assert_eq!(bisect_left_checked_at(&a, &0), (0, Some(Ordering::Greater)));
+        assert_eq!(bisect_left_checked_at(&a, &4), (3, Some(Ordering::Greater)));
+        assert_eq!(bisect_left_checked_at(&a, &6), (4, None));
+
+        for x in 0..7 {
+            let ord = bisect_left_checked_at(&a, &x).1;
+            assert_ne!(ord, Some(Ordering::Less));
+        }
+
+        // An incomparable element or target is told apart from the end by the index
+        let floats = [1.0, 2.0, 3.0];
+        assert_eq!(bisect_left_checked_at(&floats, &f64::NAN), (0, None));
+        assert_eq!(bisect_left_checked_at(&floats, &4.0), (3, None));
+        let broken = [1.0, f64::NAN, 3.0];
+        assert_eq!(bisect_left_checked_at(&broken, &2.0), (1, None));
+    }
+
+    proptest! {
+        #[test]
+        fn test_insort_right_then_bisect_right_round_trips(
+            values in prop::collection::vec(0..20_u32, 0..200)
+        ) {
+            let mut insorted = vec![];
+            for &v in &values {
+                insort_right(&mut insorted, v);
+            }
+
+            for &v in &values {
+                let i = bisect_right(&insorted, &v);
+                assert!(i > 0 && insorted[i - 1] == v);
+                assert!(i == insorted.len() || insorted[i] > v);
+            }
+
+            let mut expected = values.clone();
+            expected.sort();
+            assert_eq!(insorted, expected);
+        }
+
+        #[test]
+        fn test_insort_by_places_equal_keys(
+            keys in prop::collection::vec(0..5_u32, 0..100)
+        ) {
+            // Tag each key with its insertion order, and compare by key alone
+            let tagged: Vec<(u32, usize)> = keys.iter().cloned().zip(0..).collect();
+            let by_key = |x: &(u32, usize), y: &(u32, usize)| x.0.cmp(&y.0);
+
+            let mut left = vec![];
+            let mut right = vec![];
+            let mut left_within = vec![];
+            let mut right_within = vec![];
+            for &e in &tagged {
+                insort_left_by(&mut left, e, by_key);
+                insort_right_by(&mut right, e, by_key);
+                let len = left_within.len();
+                insort_left_slice_by(&mut left_within, e, 0..len, by_key);
+                insort_right_slice_by(&mut right_within, e, ..len, by_key);
+            }
+
+            // Equal keys stay in insertion order when inserted on the right, and reverse order
+            // when inserted on the left
+            let mut expected_right = tagged.clone();
+            expected_right.sort_by_key(|e| e.0);
+            let mut expected_left = tagged;
+            expected_left.sort_by_key(|e| (e.0, std::cmp::Reverse(e.1)));
+
+            assert_eq!(&left, &expected_left);
+            assert_eq!(&left_within, &expected_left);
+            assert_eq!(&right, &expected_right);
+            assert_eq!(&right_within, &expected_right);
+        }
+    }
+
+    #[test]
+    fn first_overlapping_matches() {
+        let intervals = [(0, 3), (2, 10), (4, 5), (6, 8), (12, 15)];
+
+        // Overlapping one
+        assert_eq!(first_overlapping(&intervals, &(12, 13)), Some(4));
+        assert_eq!(first_overlapping(&intervals, &(-5, 1)), Some(0));
+        // Overlapping several
+        assert_eq!(first_overlapping(&intervals, &(4, 7)), Some(1));
+        assert_eq!(first_overlapping(&intervals, &(1, 14)), Some(0));
+        // Overlapping none, including touching ends of half-open intervals
+        assert_eq!(first_overlapping(&intervals, &(10, 12)), None);
+        assert_eq!(first_overlapping(&intervals, &(15, 20)), None);
+        assert_eq!(first_overlapping(&intervals, &(-5, 0)), None);
+        assert_eq!(first_overlapping(&[] as &[(i32, i32)], &(0, 1)), None);
+    }
+
+    #[test]
+    fn rank_ratio_matches() {
+        let a = [1, 2, 2, 2, 3, 4, 5, 6];
+
+        assert_eq!(rank_ratio(&a, &2, RankMethod::Lower), 0.125);
+        assert_eq!(rank_ratio(&a, &2, RankMethod::Upper), 0.5);
+        assert_eq!(rank_ratio(&a, &2, RankMethod::Average), 0.3125);
+
+        // The methods agree for absent values
+        for method in &[RankMethod::Lower, RankMethod::Upper, RankMethod::Average] {
+            assert_eq!(rank_ratio(&a, &0, *method), 0.0);
+            assert_eq!(rank_ratio(&a, &10, *method), 1.0);
+            assert_eq!(rank_ratio(&[] as &[i32], &1, *method), 0.0);
+        }
+        assert_eq!(rank_ratio(&a, &6, RankMethod::Lower), 0.875);
+        assert_eq!(rank_ratio(&a, &6, RankMethod::Upper), 1.0);
+    }
+
+    #[test]
+    fn bisect_str_matches() {
+        let words = ["app", "apple", "apple", "apply", "banana", "band"];
+
+        assert_eq!(bisect_left_str(&words, "a"), 0);
+        assert_eq!(bisect_left_str(&words, "app"), 0);
+        assert_eq!(bisect_right_str(&words, "app"), 1);
+        assert_eq!(bisect_left_str(&words, "apple"), 1);
+        assert_eq!(bisect_right_str(&words, "apple"), 3);
+        assert_eq!(bisect_left_str(&words, "appl"), 1);
+        assert_eq!(bisect_left_str(&words, "ban"), 4);
+        assert_eq!(bisect_right_str(&words, "bandana"), 6);
+        assert_eq!(bisect_left_str(&words, "Zebra"), 0);
+
+        let mut owned: Vec<String> = Vec::new();
+        for w in &["band", "app", "apple", "banana", "apply", "apple"] {
+            insort_right_str(&mut owned, w.to_string());
+        }
+        assert_eq!(owned, words);
+        insort_left_str(&mut owned, "appl".to_string());
+        assert_eq!(owned[1], "appl");
+    }
+
+    #[test]
+    fn bisect_left_tolerant_refines_locally() {
+        // Globally sorted, but with 3 and 4, and 8 and 9, swapped
+        let a = [1, 2, 4, 3, 5, 6, 7, 9, 8, 10];
+        let mut sorted = a;
+        sorted.sort();
+
+        for x in 0..12 {
+            let expected = bisect_left(&sorted, &x);
+            assert_eq!(
+                bisect_left_tolerant(&a, |e| e.cmp(&x), 2),
+                expected,
+                "x {}",
+                x
+            );
+        }
+
+        // A plain bisection can be misled by the swap
+        assert_eq!(bisect_left(&a, &4), 2);
+        assert_eq!(bisect_left_tolerant(&a, |e| e.cmp(&4), 2), 3);
+
+        // On sorted data any window gives a plain bisection
+        for x in 0..12 {
+            assert_eq!(
+                bisect_left_tolerant(&sorted, |e| e.cmp(&x), 0),
+                bisect_left(&sorted, &x)
+            );
+            assert_eq!(
+                bisect_left_tolerant(&sorted, |e| e.cmp(&x), 3),
+                bisect_left(&sorted, &x)
+            );
+        }
+    }
+
+    #[test]
+    fn first_gap_matches() {
+        // Contiguous
+        assert_eq!(first_gap(&[]), None);
+        assert_eq!(first_gap(&[7]), None);
+        assert_eq!(first_gap(&[3, 4, 5, 6, 7]), None);
+        assert_eq!(first_gap(&[i64::MAX - 1, i64::MAX]), None);
+
+        // An early gap, and the first of several
+        assert_eq!(first_gap(&[3, 5, 6, 7, 8]), Some((1, 4)));
+        assert_eq!(first_gap(&[0, 1, 3, 4, 9]), Some((2, 2)));
+
+        // A late gap
+        let mut a: Vec<i64> = (100..1_000).collect();
+        a.push(1_001);
+        assert_eq!(first_gap(&a), Some((900, 1_000)));
+        assert_eq!(first_gap(&[i64::MIN, i64::MAX]), Some((1, i64::MIN + 1)));
+    }
+
+    #[test]
+    fn bisect_constant_slice_matches_general_path() {
+        for len in 1..5 {
+            let a = vec![5; len];
+            for x in 4..7 {
+                assert_eq!(bisect_left_uniform_aware(&a, &x), bisect_left(&a, &x));
+                assert_eq!(bisect_right_uniform_aware(&a, &x), bisect_right(&a, &x));
+            }
+            assert_eq!(bisect_left_uniform_aware(&a, &5), 0);
+            assert_eq!(bisect_right_uniform_aware(&a, &5), len);
+        }
+        // A slice that isn't constant takes the general path
+        let a = [1, 3, 3, 5];
+        for x in 0..7 {
+            assert_eq!(bisect_left_uniform_aware(&a, &x), bisect_left(&a, &x));
+            assert_eq!(bisect_right_uniform_aware(&a, &x), bisect_right(&a, &x));
+        }
+        assert_eq!(bisect_left_uniform_aware(&[] as &[i32], &1), 0);
+        assert_eq!(bisect_right_uniform_aware(&[] as &[i32], &1), 0);
+    }
+
+    #[test]
+    fn distinct_count_lt_matches() {
+        let a = [1, 1, 1, 2, 2, 5, 5, 5, 5, 9];
+
+        assert_eq!(distinct_count_lt(&a, &0), 0);
+        assert_eq!(distinct_count_lt(&a, &1), 0);
+        assert_eq!(distinct_count_lt(&a, &2), 1);
+        assert_eq!(distinct_count_lt(&a, &5), 2);
+        assert_eq!(distinct_count_lt(&a, &6), 3);
+        assert_eq!(distinct_count_lt(&a, &10), 4);
+        assert_eq!(count_lt(&a, &6), 9);
+        assert_eq!(distinct_count_lt(&[] as &[i32], &1), 0);
+    }
+
+    #[test]
+    fn bisect_f64_endpoints() {
+        let a = [-1.0, -0.0, 0.0, 0.5, 0.5, 2.0];
+
+        assert_eq!(bisect_f64(&a, 0.5, Endpoint::Inclusive), 5);
+        assert_eq!(bisect_f64(&a, 0.5, Endpoint::Exclusive), 3);
+        assert_eq!(bisect_f64(&a, 2.0, Endpoint::Inclusive), 6);
+        assert_eq!(bisect_f64(&a, 2.0, Endpoint::Exclusive), 5);
+        assert_eq!(bisect_f64(&a, 1.0, Endpoint::Inclusive), 5);
+        assert_eq!(bisect_f64(&a, 1.0, Endpoint::Exclusive), 5);
+
+        // Both zeros are equal to either zero
+        for &zero in &[0.0, -0.0] {
+            assert_eq!(bisect_f64(&a, zero, Endpoint::Inclusive), 3);
+            assert_eq!(bisect_f64(&a, zero, Endpoint::Exclusive), 1);
+        }
+    }
+
+    #[cfg(feature = "capi")]
+    #[test]
+    fn capi_wrappers_match_bisect_left() {
+        let a = [1, 3, 3, 5];
+        assert_eq!(bisect_left_c(&a, &3), SearchResultC { index: 1, found: 1 });
+        assert_eq!(bisect_left_c(&a, &4), SearchResultC { index: 3, found: 0 });
+        assert_eq!(bisect_left_c(&a, &6), SearchResultC { index: 4, found: 0 });
+
+        for x in 0..7 {
+            let result = unsafe { bisect_left_i32_capi(a.as_ptr(), a.len(), x) };
+            assert_eq!(result, bisect_left_c(&a, &x));
+            let wide: Vec<i64> = a.iter().map(|&e| i64::from(e)).collect();
+            let result = unsafe { bisect_left_i64_capi(wide.as_ptr(), wide.len(), i64::from(x)) };
+            assert_eq!(result, bisect_left_c(&a, &x));
+        }
+
+        let floats = [0.5, 1.5, 2.5];
+        let result = unsafe { bisect_left_f64_capi(floats.as_ptr(), floats.len(), 1.5) };
+        assert_eq!(result, SearchResultC { index: 1, found: 1 });
+        let result = unsafe { bisect_left_u32_capi(std::ptr::null(), 0, 1) };
+        assert_eq!(result, SearchResultC { index: 0, found: 0 });
+    }
+
+    #[test]
+    fn bisect_left_slice_local_matches() {
+        let a = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+
+        for &(lo, hi) in &[(0, 10), (3, 8), (5, 6), (9, 10), (4, 4)] {
+            for x in 0..12 {
+                let global = bisect_left_slice(&a, &x, lo..hi);
+                let local = bisect_left_slice_local(&a, &x, lo..hi);
+                assert_eq!(local, global - lo);
+                assert_eq!(local, bisect_left(&a[lo..hi], &x));
+            }
+        }
+        assert_eq!(bisect_left_slice_local(&a, &0, 3..8), 0);
+        assert_eq!(bisect_left_slice_local(&a, &4, 3..8), 0);
+        assert_eq!(bisect_left_slice_local(&a, &6, 3..=7), 2);
+        assert_eq!(bisect_left_slice_local(&a, &20, 3..8), 5);
+    }
+
+    proptest! {
+        #[test]
+        fn test_bounds_vs_bisect(
+            mut nums in prop::collection::vec(0..20_u32, 0..100),
+            num in 0..22_u32,
+            lo in 0..100_usize,
+            hi in 0..100_usize
+        ) {
+            nums.sort();
+            let (lo, hi) = (std::cmp::min(lo, hi), std::cmp::max(lo, hi));
+            let (lo, hi) = (std::cmp::min(lo, nums.len()), std::cmp::min(hi, nums.len()));
+            let cmp = |e: &u32| e.cmp(&num);
+
+            assert_eq!(lower_bound(&nums, &num), bisect_left(&nums, &num));
+            assert_eq!(upper_bound(&nums, &num), bisect_right(&nums, &num));
+            assert_eq!(lower_bound_by(&nums, cmp), bisect_left(&nums, &num));
+            assert_eq!(upper_bound_by(&nums, cmp), bisect_right(&nums, &num));
+            let left = bisect_left_slice(&nums, &num, lo..hi);
+            let right = bisect_right_slice(&nums, &num, lo..hi);
+            assert_eq!(lower_bound_slice(&nums, &num, lo..hi), left);
+            assert_eq!(upper_bound_slice(&nums, &num, lo..hi), right);
+            assert_eq!(lower_bound_slice_by(&nums, lo..hi, cmp), left);
+            assert_eq!(upper_bound_slice_by(&nums, lo..hi, cmp), right);
+        }
+    }
+
+    #[test]
+    fn validate_sorted_matches() {
+        assert_eq!(validate_sorted(&[1, 2, 2, 3]), Ok(()));
+        assert_eq!(validate_sorted::<i32>(&[]), Ok(()));
+        assert_eq!(validate_sorted(&[4]), Ok(()));
+        assert_eq!(validate_sorted(&[4, 3, 2, 1]), Err(1));
+        assert_eq!(validate_sorted(&[1, 2, 5, 4, 6]), Err(3));
+        assert_eq!(validate_sorted(&[0.5, 1.5, 1.0]), Err(2));
+        assert_eq!(validate_sorted(&[3.0, f64::NAN, 1.0]), Err(1));
+        assert_eq!(validate_sorted(&[1.0, 2.0, f64::NAN]), Err(2));
+        assert_eq!(validate_sorted(&[f64::NAN]), Ok(()));
+
+        let rows = [(1, 'z'), (2, 'a'), (2, 'b'), (1, 'a')];
+        assert_eq!(validate_sorted_by(&rows, |x, y| x.0.cmp(&y.0)), Err(3));
+        assert_eq!(validate_sorted_by(&rows[..3], |x, y| x.0.cmp(&y.0)), Ok(()));
+        assert_eq!(validate_sorted_by(&[3, 2, 1], |x, y| y.cmp(x)), Ok(()));
+    }
+
+    #[test]
+    fn bisect_left_skip_sentinel_matches() {
+        const EMPTY: i32 = i32::MIN;
+        let a = [EMPTY, EMPTY, EMPTY, 3, 5, 5, 8];
+
+        assert_eq!(bisect_left_skip_sentinel(&a, 0, EMPTY), 3);
+        assert_eq!(bisect_left_skip_sentinel(&a, 5, EMPTY), 4);
+        assert_eq!(bisect_left_skip_sentinel(&a, 6, EMPTY), 6);
+        assert_eq!(bisect_left_skip_sentinel(&a, 9, EMPTY), 7);
+        assert_eq!(bisect_left_skip_sentinel(&a, EMPTY, EMPTY), 3);
+
+        // No sentinels, or nothing but sentinels
+        let b = [3, 5, 5, 8];
+        for x in 0..10 {
+            assert_eq!(bisect_left_skip_sentinel(&b, x, EMPTY), bisect_left(&b, &x));
+        }
+        assert_eq!(bisect_left_skip_sentinel(&[EMPTY; 3], 4, EMPTY), 3);
+
+        // A sentinel that isn't the minimum value
+        let c = [-1, -1, 2, 4];
+        assert_eq!(bisect_left_skip_sentinel(&c, -5, -1), 2);
+        assert_eq!(bisect_left_skip_sentinel(&c, 3, -1), 3);
+    }
+
+    #[test]
+    fn multi_bin_ranges_matches() {
+        let a = [1, 2, 2, 4, 5, 7, 8, 9, 12];
+
+        let ranges = multi_bin_ranges(&a, &[2, 4, 8, 10]);
+        assert_eq!(ranges, vec![1..3, 3..6, 6..8]);
+        assert_eq!(&a[ranges[1].clone()], &[4, 5, 7]);
+
+        // Contiguous, and covering everything between the first and last edge
+        let ranges = multi_bin_ranges(&a, &[0, 3, 3, 9, 20]);
+        assert!(ranges.windows(2).all(|w| w[0].end == w[1].start));
+        assert_eq!(ranges.first().unwrap().start, 0);
+        assert_eq!(ranges.last().unwrap().end, a.len());
+        assert_eq!(ranges[1], 3..3);
+
+        assert!(multi_bin_ranges(&a, &[5]).is_empty());
+        assert!(multi_bin_ranges(&a, &[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "bin edges must be sorted")]
+    fn multi_bin_ranges_panics_on_unsorted_edges() {
+        multi_bin_ranges(&[1, 2, 3], &[3, 1]);
+    }
+
+    #[test]
+    fn bisect_find_eq_matches() {
+        let words = ["apple", "Bob", "bob", "BOB", "carrot"];
+        let ord =
+            |target: &'static str| move |w: &&str| w.to_lowercase().cmp(&target.to_lowercase());
+
+        assert_eq!(bisect_find_eq(&words, ord("bob"), |w| *w == "bob"), Some(2));
+        assert_eq!(bisect_find_eq(&words, ord("bob"), |w| *w == "BOB"), Some(3));
+        assert_eq!(bisect_find_eq(&words, ord("bob"), |w| *w == "bOb"), None);
+        assert_eq!(
+            bisect_find_eq(&words, ord("Apple"), |w| *w == "apple"),
+            Some(0)
+        );
+        assert_eq!(bisect_find_eq(&words, ord("dog"), |_| true), None);
+    }
+
+    proptest! {
+        #[test]
+        fn test_monotone_cursor_vs_bisect_left(
+            mut nums in prop::collection::vec(0..100_u32, 0..200),
+            mut targets in prop::collection::vec(0..110_u32, 0..100)
+        ) {
+            nums.sort();
+            targets.sort();
+
+            let mut cursor = MonotoneCursor::new();
+            for t in &targets {
+                assert_eq!(cursor.search(&nums, t), bisect_left(&nums, t));
+                assert_eq!(cursor.index(), bisect_left(&nums, t));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "targets must be non-decreasing")]
+    fn monotone_cursor_rejects_decreasing_targets() {
+        let a = [1, 2, 3, 4, 5];
+        let mut cursor = MonotoneCursor::new();
+        cursor.search(&a, &4);
+        cursor.search(&a, &2);
+    }
+
+    #[test]
+    fn sample_index_matches() {
+        // Weights 1, 2, 0, 3
+        let cumulative = [1.0, 3.0, 3.0, 6.0];
+
+        // Interior values
+        assert_eq!(sample_index(&cumulative, 0.0), 0);
+        assert_eq!(sample_index(&cumulative, 0.5), 0);
+        assert_eq!(sample_index(&cumulative, 2.9), 1);
+        assert_eq!(sample_index(&cumulative, 4.5), 3);
+
+        // Boundaries fall in the next bin, skipping the empty one
+        assert_eq!(sample_index(&cumulative, 1.0), 1);
+        assert_eq!(sample_index(&cumulative, 3.0), 3);
+
+        // Clamped at the total
+        assert_eq!(sample_index(&cumulative, 6.0), 3);
+        assert_eq!(sample_index(&cumulative, 7.0), 3);
+    }
+
+    #[test]
+    fn floor_ceil_matches() {
+        let grid = [0, 10, 20, 20, 30];
+
+        // On the grid
+        assert_eq!(floor_ceil(&grid, &10), (Some(1), Some(1)));
+        assert_eq!(floor_ceil(&grid, &20), (Some(3), Some(2)));
+        assert_eq!(floor_ceil(&grid, &0), (Some(0), Some(0)));
+        assert_eq!(floor_ceil(&grid, &30), (Some(4), Some(4)));
+
+        // Between grid points
+        assert_eq!(floor_ceil(&grid, &15), (Some(1), Some(2)));
+        assert_eq!(floor_ceil(&grid, &25), (Some(3), Some(4)));
+
+        // Out of range
+        assert_eq!(floor_ceil(&grid, &-5), (None, Some(0)));
+        assert_eq!(floor_ceil(&grid, &35), (Some(4), None));
+        assert_eq!(floor_ceil(&[] as &[i32], &1), (None, None));
+    }
+
+    #[test]
+    fn bisect_shared_across_threads() {
+        let a: Arc<[i32]> = (0..1_000).map(|i| i / 2).collect::<Vec<_>>().into();
+
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let a = Arc::clone(&a);
+                std::thread::spawn(move || {
+                    for x in (t..500).step_by(4) {
+                        assert_eq!(bisect_left_shared(&a, &x), 2 * x as usize);
+                        assert_eq!(bisect_right_shared(&a, &x), 2 * x as usize + 2);
+                        assert_eq!(bisect_left_shared_by(&a, |e| e.cmp(&x)), 2 * x as usize);
+                        assert_eq!(bisect_right_shared_by(&a, |e| x.cmp(e)), 2 * x as usize + 2);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_bisect_left_bucketed_vs_bisect_left(
+            mut nums in prop::collection::vec(0..100_u32, 0..300),
+            mut buckets in prop::collection::vec(0..300_usize, 0..20),
+            num in 0..110_u32
+        ) {
+            nums.sort();
+            for b in buckets.iter_mut() {
+                *b = std::cmp::min(*b, nums.len());
+            }
+            buckets.sort();
+
+            assert_eq!(bisect_left_bucketed(&nums, &buckets, &num), bisect_left(&nums, &num));
+        }
+    }
+
+    #[test]
+    fn bisect_left_bucketed_matches() {
+        let a: Vec<u32> = (0..64).map(|i| i * 3).collect();
+        let buckets: Vec<usize> = (0..64).step_by(8).collect();
+
+        for x in 0..200 {
+            assert_eq!(bisect_left_bucketed(&a, &buckets, &x), bisect_left(&a, &x));
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "bucket boundaries must be non-decreasing")]
+    fn bisect_left_bucketed_panics_on_unsorted_buckets() {
+        bisect_left_bucketed(&[1, 2, 3, 4], &[0, 3, 2], &2);
+    }
 
-    const RIGHT_FLOAT_CASES: TestCollection<f32> = &[
-        t!(floats_right_0, &[], 1.0, 0),
-        t!(floats_right_1, &[1.0], 0.0, 0),
-        t!(floats_right_2, &[1.0], 1.0, 1),
-        t!(floats_right_3, &[1.0], 2.0, 1),
-        t!(floats_right_4, &[1.0, 1.0], 0.0, 0),
-        t!(floats_right_5, &[1.0, 1.0], 1.0, 2),
-        t!(floats_right_6, &[1.0, 1.0], 2.0, 2),
-        t!(floats_right_7, &[1.0, 1.0, 1.0], 0.0, 0),
-        t!(floats_right_8, &[1.0, 1.0, 1.0], 1.0, 3),
-        t!(floats_right_9, &[1.0, 1.0, 1.0], 2.0, 3),
-        t!(floats_right_10, &[1.0, 1.0, 1.0, 1.0], 0.0, 0),
-        t!(floats_right_11, &[1.0, 1.0, 1.0, 1.0], 1.0, 4),
-        t!(floats_right_12, &[1.0, 1.0, 1.0, 1.0], 2.0, 4),
-        t!(floats_right_13, &[1.0, 2.0], 0.0, 0),
-        t!(floats_right_14, &[1.0, 2.0], 1.0, 1),
-        t!(floats_right_15, &[1.0, 2.0], 2.0, 2),
-        t!(floats_right_16, &[1.0, 2.0], 3.0, 2),
-        t!(floats_right_17, &[1.0, 1.0, 2.0, 2.0], 0.0, 0),
-        t!(floats_right_18, &[1.0, 1.0, 2.0, 2.0], 1.0, 2),
-        t!(floats_right_19, &[1.0, 1.0, 2.0, 2.0], 2.0, 4),
-        t!(floats_right_20, &[1.0, 1.0, 2.0, 2.0], 3.0, 4),
-        t!(floats_right_21, &[1.0, 2.0, 3.0], 0.0, 0),
-        t!(floats_right_22, &[1.0, 2.0, 3.0], 1.0, 1),
-        t!(floats_right_23, &[1.0, 2.0, 3.0], 2.0, 2),
-        t!(floats_right_24, &[1.0, 2.0, 3.0], 3.0, 3),
-        t!(floats_right_25, &[1.0, 2.0, 3.0], 4.0, 3),
-        t!(floats_right_26, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 0.0, 0),
-        t!(floats_right_27, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 1.0, 1),
-        t!(floats_right_28, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 2.0, 3),
-        t!(floats_right_29, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 3.0, 6),
-        t!(floats_right_30, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 4.0, 10),
-        t!(floats_right_31, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 5.0, 10),
-    ];
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "bucket boundary out of bounds")]
+    fn bisect_left_bucketed_panics_on_out_of_bounds_bucket() {
+        bisect_left_bucketed(&[1, 2, 3, 4], &[0, 2, 5], &2);
+    }
 
-    const LEFT_FLOAT_CASES: TestCollection<f32> = &[
-        t!(floats_left_0, &[], 1.0, 0),
-        t!(floats_left_1, &[1.0], 0.0, 0),
-        t!(floats_left_2, &[1.0], 1.0, 0),
-        t!(floats_left_3, &[1.0], 2.0, 1),
-        t!(floats_left_4, &[1.0, 1.0], 0.0, 0),
-        t!(floats_left_5, &[1.0, 1.0], 1.0, 0),
-        t!(floats_left_6, &[1.0, 1.0], 2.0, 2),
-        t!(floats_left_7, &[1.0, 1.0, 1.0], 0.0, 0),
-        t!(floats_left_8, &[1.0, 1.0, 1.0], 1.0, 0),
-        t!(floats_left_9, &[1.0, 1.0, 1.0], 2.0, 3),
-        t!(floats_left_10, &[1.0, 1.0, 1.0, 1.0], 0.0, 0),
-        t!(floats_left_11, &[1.0, 1.0, 1.0, 1.0], 1.0, 0),
-        t!(floats_left_12, &[1.0, 1.0, 1.0, 1.0], 2.0, 4),
-        t!(floats_left_13, &[1.0, 2.0], 0.0, 0),
-        t!(floats_left_14, &[1.0, 2.0], 1.0, 0),
-        t!(floats_left_15, &[1.0, 2.0], 2.0, 1),
-        t!(floats_left_16, &[1.0, 2.0], 3.0, 2),
-        t!(floats_left_17, &[1.0, 1.0, 2.0, 2.0], 0.0, 0),
-        t!(floats_left_18, &[1.0, 1.0, 2.0, 2.0], 1.0, 0),
-        t!(floats_left_19, &[1.0, 1.0, 2.0, 2.0], 2.0, 2),
-        t!(floats_left_20, &[1.0, 1.0, 2.0, 2.0], 3.0, 4),
-        t!(floats_left_21, &[1.0, 2.0, 3.0], 0.0, 0),
-        t!(floats_left_22, &[1.0, 2.0, 3.0], 1.0, 0),
-        t!(floats_left_23, &[1.0, 2.0, 3.0], 2.0, 1),
-        t!(floats_left_24, &[1.0, 2.0, 3.0], 3.0, 2),
-        t!(floats_left_25, &[1.0, 2.0, 3.0], 4.0, 3),
-        t!(floats_left_26, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 0.0, 0),
-        t!(floats_left_27, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 1.0, 0),
-        t!(floats_left_28, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 2.0, 1),
-        t!(floats_left_29, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 3.0, 3),
-        t!(floats_left_30, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 4.0, 6),
-        t!(floats_left_31, &[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0, 4.0, 4.0, 4.0], 5.0, 10),
-    ];
+    #[test]
+    fn found_index_signed_matches() {
+        let a = [1, 3, 3, 5];
+
+        assert_eq!(found_index_signed(&a, &1), 0);
+        assert_eq!(found_index_signed(&a, &3), 1);
+        assert_eq!(found_index_signed(&a, &5), 3);
+        assert_eq!(found_index_signed(&a, &0), -1);
+        assert_eq!(found_index_signed(&a, &4), -1);
+        assert_eq!(found_index_signed(&a, &6), -1);
+        assert_eq!(found_index_signed(&[] as &[i32], &1), -1);
+    }
+
+    #[test]
+    fn neighborhood_matches() {
+        let a = [0, 10, 20, 30, 40, 50, 60, 70];
+
+        // Interior
+        assert_eq!(neighborhood(&a, &35, 2), 2..6);
+        assert_eq!(neighborhood(&a, &30, 1), 2..4);
+        assert_eq!(neighborhood(&a, &35, 0), 4..4);
+
+        // Clamped near either edge
+        assert_eq!(neighborhood(&a, &5, 2), 0..3);
+        assert_eq!(neighborhood(&a, &-5, 2), 0..2);
+        assert_eq!(neighborhood(&a, &65, 2), 5..8);
+        assert_eq!(neighborhood(&a, &100, 2), 6..8);
+
+        // Radius larger than the slice
+        assert_eq!(neighborhood(&a, &35, 20), 0..8);
+        assert_eq!(neighborhood(&a, &35, usize::MAX), 0..8);
+        assert_eq!(neighborhood(&[] as &[i32], &1, 3), 0..0);
+    }
+
+    #[test]
+    fn insort_combine_sums_counts() {
+        // Counts are ordered and compared by word alone
+        #[derive(Debug)]
+        struct Count(&'static str, u32);
+        impl PartialEq for Count {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Count {}
+        impl PartialOrd for Count {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Count {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.cmp(other.0)
+            }
+        }
+
+        let mut counts = Vec::new();
+        let cases = [
+            ("b", 0, 1),
+            ("a", 0, 2),
+            ("b", 1, 2),
+            ("c", 2, 3),
+            ("b", 1, 3),
+            ("a", 0, 3),
+        ];
+        for &(word, index, len) in cases.iter() {
+            let i = insort_combine(&mut counts, Count(word, 1), |e, x| e.1 += x.1);
+            assert_eq!(i, index);
+            assert_eq!(counts.len(), len);
+        }
+        let counts: Vec<_> = counts.iter().map(|c| (c.0, c.1)).collect();
+        assert_eq!(counts, vec![("a", 2), ("b", 3), ("c", 1)]);
+    }
+
+    #[test]
+    fn bisect_left_rev_view_mirrors_forward_index() {
+        let a = [1, 2, 2, 3, 5];
+        let rev: Vec<_> = a.iter().rev().cloned().collect();
+        for x in 0..7 {
+            let i = bisect_left_rev_view(&a, &x);
+            assert_eq!(i + bisect_right(&a, &x), a.len());
+            // Matches a left bisection of the descending copy
+            assert_eq!(i, rev.iter().take_while(|&&y| y > x).count());
+        }
+        assert_eq!(bisect_left_rev_view(&a, &6), 0);
+        assert_eq!(bisect_left_rev_view(&a, &0), a.len());
+        assert_eq!(bisect_left_rev_view(&a, &2), 2);
+        assert_eq!(bisect_left_rev_view(&[] as &[i32], &2), 0);
+    }
+
+    #[test]
+    fn keyed_pair_compares_keys_only() {
+        let mut a: Vec<(i32, String)> = Vec::new();
+        for (k, v) in &[(2, "b"), (1, "z"), (2, "a"), (3, "c"), (2, "c")] {
+            insort_right_keyed_pair(&mut a, *k, v.to_string());
+        }
+        let names: Vec<_> = a.iter().map(|e| e.1.as_str()).collect();
+        // Equal keys keep insertion order, payloads are ignored
+        assert_eq!(names, vec!["z", "b", "a", "c", "c"]);
+
+        assert_eq!(bisect_left_keyed_pair(&a, &2), 1);
+        assert_eq!(bisect_right_keyed_pair(&a, &2), 4);
+        assert_eq!(equal_range_keyed_pair(&a, &2), 1..4);
+        assert_eq!(equal_range_keyed_pair(&a, &0), 0..0);
+        assert_eq!(equal_range_keyed_pair(&a, &4), 5..5);
+        let twos: Vec<_> = a[equal_range_keyed_pair(&a, &2)]
+            .iter()
+            .map(|e| &e.1[..])
+            .collect();
+        assert_eq!(twos, vec!["b", "a", "c"]);
+
+        insort_left_keyed_pair(&mut a, 2, "first".to_string());
+        assert_eq!(a[1].1, "first");
+        assert_eq!(equal_range_keyed_pair(&a, &2), 1..5);
+    }
+
+    #[test]
+    fn bisect_left_spotcheck_detects_inversion() {
+        let a: Vec<_> = (0..20).collect();
+        for x in -1..21 {
+            assert_eq!(
+                bisect_left_spotcheck(&a, &x, 8),
+                (bisect_left(&a, &x), true)
+            );
+        }
+        assert_eq!(bisect_left_spotcheck(&[] as &[i32], &1, 8), (0, true));
+        assert_eq!(bisect_left_spotcheck(&[1], &1, 8), (0, true));
+
+        // Enough samples hit the one inverted pair
+        let mut b = a.clone();
+        b.swap(11, 12);
+        assert!(!bisect_left_spotcheck(&b, &5, 200).1);
+        // No samples check nothing
+        assert!(bisect_left_spotcheck(&b, &5, 0).1);
+    }
+
+    #[test]
+    fn bisect_left_ringbuf_matches_unrolled() {
+        let buf = [7, 9, 9, 1, 3, 3, 5];
+        for &(head, len) in [(3, 7), (3, 5), (0, 3), (5, 4), (6, 1), (2, 0)].iter() {
+            let unrolled: Vec<_> = (0..len).map(|i| buf[(head + i) % buf.len()]).collect();
+            assert!(unrolled.windows(2).all(|w| w[0] <= w[1]), "{:?}", unrolled);
+            for x in 0..11 {
+                assert_eq!(
+                    bisect_left_ringbuf(&buf, head, len, &x),
+                    bisect_left(&unrolled, &x),
+                    "head {}, len {}, x {}",
+                    head,
+                    len,
+                    x
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds capacity")]
+    fn bisect_left_ringbuf_panics_past_capacity() {
+        bisect_left_ringbuf(&[1, 2], 0, 3, &1);
+    }
+
+    #[test]
+    fn safe_bisect_left_catches_inversions_on_probe_path() {
+        let a: Vec<_> = (0..16).collect();
+        for x in -1..17 {
+            assert_eq!(safe_bisect_left(&a, &x), Ok(bisect_left(&a, &x)));
+        }
+        assert_eq!(safe_bisect_left(&[] as &[i32], &1), Ok(0));
+
+        // The first probe, index 8, sees its right neighbour out of order
+        let mut b = a.clone();
+        b.swap(8, 9);
+        assert_eq!(safe_bisect_left(&b, &3), Err(SortError { index: 8 }));
+        // Index 12 is probed after 8 and is below its left bracket, `b[8]`
+        let mut c = a.clone();
+        c[12] = 2;
+        assert_eq!(safe_bisect_left(&c, &11), Err(SortError { index: 12 }));
+        assert_eq!(
+            SortError { index: 12 }.to_string(),
+            "slice is not sorted around index 12"
+        );
+    }
+
+    #[test]
+    fn lcp_range_finds_longest_shared_prefix() {
+        let a = [
+            "apple", "applet", "apply", "banana", "band", "bandana", "can",
+        ];
+        assert_eq!(lcp_range(&a, "applesauce"), (5, 0..2));
+        assert_eq!(lcp_range(&a, "appz"), (3, 0..3));
+        assert_eq!(lcp_range(&a, "bandit"), (4, 4..6));
+        assert_eq!(lcp_range(&a, "band"), (4, 4..6));
+        assert_eq!(lcp_range(&a, "bx"), (1, 3..6));
+        assert_eq!(lcp_range(&a, "zebra"), (0, 0..7));
+        assert_eq!(lcp_range(&a, ""), (0, 0..7));
+        assert_eq!(lcp_range(&[], "a"), (0, 0..0));
+        // Multi-byte characters are never split
+        assert_eq!(lcp_range(&["né", "në"], "nê"), (1, 0..2));
+    }
+
+    #[test]
+    fn guarded_bisect_matches_unguarded() {
+        let cases: [&[i32]; 5] = [&[], &[3], &[3, 3], &[1, 2, 2, 4], &[1, 3, 3, 3, 5, 7, 7]];
+        for a in cases.iter() {
+            for x in -1..9 {
+                assert_eq!(
+                    bisect_left_guarded(a, &x),
+                    bisect_left(a, &x),
+                    "{:?} {}",
+                    a,
+                    x
+                );
+                assert_eq!(
+                    bisect_right_guarded(a, &x),
+                    bisect_right(a, &x),
+                    "{:?} {}",
+                    a,
+                    x
+                );
+            }
+        }
+
+        // Targets incomparable with all or some of the elements
+        let floats: [&[f64]; 4] = [&[], &[2.0], &[1.0, 2.0, 3.0], &[1.0, 2.0, 2.0, 2.0, 5.0]];
+        for a in floats.iter() {
+            for x in [f64::NAN, 0.0, 2.0, 6.0] {
+                let (left, right) = (bisect_left(a, &x), bisect_right(a, &x));
+                assert_eq!(bisect_left_guarded(a, &x), left, "{:?} {}", a, x);
+                assert_eq!(bisect_right_guarded(a, &x), right, "{:?} {}", a, x);
+            }
+        }
+        let pairs = [(1.0, 0.0), (2.0, 0.0), (2.0, 1.0), (3.0, 0.0), (4.0, 0.0)];
+        for x in [
+            (2.0, f64::NAN),
+            (1.0, f64::NAN),
+            (4.0, f64::NAN),
+            (f64::NAN, 0.0),
+        ] {
+            let (left, right) = (bisect_left(&pairs, &x), bisect_right(&pairs, &x));
+            assert_eq!(bisect_left_guarded(&pairs, &x), left, "{:?}", x);
+            assert_eq!(bisect_right_guarded(&pairs, &x), right, "{:?}", x);
+        }
+    }
+
+    #[test]
+    fn rank_and_prefix_sum_matches_manual_sum() {
+        let a = [0.5, 1.0, 1.0, 2.5, 4.0];
+        let mut cumulative = vec![0.0];
+        for e in a.iter() {
+            cumulative.push(cumulative[cumulative.len() - 1] + e);
+        }
+        for &x in [0.0, 0.5, 1.0, 1.5, 2.5, 3.0, 4.0, 5.0].iter() {
+            let (i, sum) = rank_and_prefix_sum(&a, &cumulative, x);
+            assert_eq!(i, a.iter().filter(|&&e| e < x).count());
+            assert_eq!(sum, a[..i].iter().sum::<f64>());
+        }
+        assert_eq!(rank_and_prefix_sum(&[], &[0.0], 1.0), (0, 0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "one more element")]
+    fn rank_and_prefix_sum_checks_lengths() {
+        rank_and_prefix_sum(&[1.0, 2.0], &[0.0, 1.0], 1.5);
+    }
+
+    #[test]
+    fn big_endian_bisect_matches_numeric() {
+        let keys = [0_u64, 1, 255, 256, 65_536, 1 << 40, u64::MAX];
+        let pages: Vec<_> = keys.iter().map(|k| k.to_be_bytes()).collect();
+        for &x in [0_u64, 2, 255, 257, 1 << 32, 1 << 40, u64::MAX - 1, u64::MAX].iter() {
+            assert_eq!(
+                bisect_left_be_u64(&pages, x),
+                bisect_left(&keys, &x),
+                "{}",
+                x
+            );
+        }
+
+        let keys = [3_u32, 256, 70_000, u32::MAX];
+        let pages: Vec<_> = keys.iter().map(|k| k.to_be_bytes()).collect();
+        for &x in [0_u32, 3, 255, 257, 70_000, u32::MAX].iter() {
+            assert_eq!(
+                bisect_left_be_u32(&pages, x),
+                bisect_left(&keys, &x),
+                "{}",
+                x
+            );
+        }
+
+        let keys = [i64::MIN, -256, -1, 0, 1, 255, i64::MAX];
+        let pages: Vec<_> = keys
+            .iter()
+            .map(|&k| (k as u64 ^ 1 << 63).to_be_bytes())
+            .collect();
+        assert!(pages.windows(2).all(|w| w[0] < w[1]));
+        for &x in [i64::MIN, -300, -2, -1, 0, 2, 256, i64::MAX].iter() {
+            assert_eq!(
+                bisect_left_be_i64(&pages, x),
+                bisect_left(&keys, &x),
+                "{}",
+                x
+            );
+        }
+    }
+
+    #[test]
+    fn insort_context_right_clamps_at_ends() {
+        let a = [10, 20, 30, 40, 50, 60];
+        assert_eq!(insort_context_right(&a, &35, 4), (3, vec![20, 30, 40, 50]));
+        assert_eq!(insort_context_right(&a, &30, 3), (3, vec![30, 40, 50]));
+        assert_eq!(insort_context_right(&a, &5, 4), (0, vec![10, 20]));
+        assert_eq!(insort_context_right(&a, &15, 4), (1, vec![10, 20, 30]));
+        assert_eq!(insort_context_right(&a, &70, 4), (6, vec![50, 60]));
+        assert_eq!(insort_context_right(&a, &35, 0), (3, vec![]));
+        assert_eq!(insort_context_right(&a, &35, 100), (3, a.to_vec()));
+        assert_eq!(insort_context_right(&[], &1, 4), (0, vec![]));
+    }
+
+    #[test]
+    fn inlined_searches_match_linear_scan() {
+        let a: Vec<u64> = (0..200).map(|i| i / 3 * 2).collect();
+        for t in 0..140 {
+            let lt = a.iter().filter(|&&e| e < t).count();
+            let le = a.iter().filter(|&&e| e <= t).count();
+            assert_eq!(bisect_left(&a, &t), lt);
+            assert_eq!(bisect_right(&a, &t), le);
+            assert_eq!(bisect_left_by(&a, |e| e.cmp(&t)), lt);
+            assert_eq!(bisect_right_by(&a, |e| t.cmp(e)), le);
+            assert_eq!(bisect_left_slice(&a, &t, 10..150), lt.clamp(10, 150));
+            assert_eq!(bisect_right_slice(&a, &t, 10..150), le.clamp(10, 150));
+        }
+    }
+
+    #[test]
+    fn bisect_by_resolution_splits_equal_run() {
+        // Events keyed by time, with ties broken by a sequence number the comparator knows about
+        let a = [(1, 0), (2, 0), (2, 1), (2, 2), (2, 3), (3, 0)];
+        let resolve = |time: i32, before_seq: i32| {
+            move |e: &(i32, i32)| match e.0.cmp(&time) {
+                Ordering::Less => Resolution::Less,
+                Ordering::Greater => Resolution::Greater,
+                Ordering::Equal if e.1 < before_seq => Resolution::PreferRight,
+                Ordering::Equal => Resolution::PreferLeft,
+            }
+        };
+        assert_eq!(bisect_by_resolution(&a, resolve(2, 2)), 3);
+        assert_eq!(bisect_by_resolution(&a, resolve(2, 0)), 1);
+        assert_eq!(bisect_by_resolution(&a, resolve(2, 9)), 5);
+        assert_eq!(bisect_by_resolution(&a, resolve(0, 0)), 0);
+        assert_eq!(bisect_by_resolution(&a, resolve(4, 0)), 6);
+
+        // Always preferring one side matches the left and right searches
+        let left = bisect_by_resolution(&a, |e| match e.0.cmp(&2) {
+            Ordering::Less => Resolution::Less,
+            Ordering::Equal => Resolution::PreferLeft,
+            Ordering::Greater => Resolution::Greater,
+        });
+        let right = bisect_by_resolution(&a, |e| match e.0.cmp(&2) {
+            Ordering::Less => Resolution::Less,
+            Ordering::Equal => Resolution::PreferRight,
+            Ordering::Greater => Resolution::Greater,
+        });
+        assert_eq!((left, right), (1, 5));
+        assert_eq!(bisect_by_resolution(&[] as &[i32], |_| Resolution::Less), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn test_ranks_of_matches_per_query(
+            mut nums in prop::collection::vec(0..100_u32, 0..200),
+            queries in prop::collection::vec(0..110_u32, 0..50)
+        ) {
+            nums.sort();
+            let naive: Vec<_> = queries.iter().map(|q| bisect_left(&nums, q)).collect();
+            assert_eq!(ranks_of(&nums, &queries), naive);
+        }
+    }
+
+    #[test]
+    fn ranks_of_falls_back_on_incomparable_queries() {
+        let a = [1.0, 2.0, 3.0];
+        assert_eq!(ranks_of(&a, &[2.5, f64::NAN, 0.5, 2.0]), vec![2, 0, 0, 1]);
+    }
+
+    #[test]
+    fn ranks_of_falls_back_on_large_batch_with_nan() {
+        let a: Vec<f64> = (0..100).map(f64::from).collect();
+        let mut queries: Vec<f64> = (0..64).map(|i| f64::from((i * 37) % 101) - 0.5).collect();
+        queries[17] = f64::NAN;
+        queries[50] = f64::NAN;
+        let expected: Vec<usize> = queries.iter().map(|q| bisect_left(&a, q)).collect();
+        assert_eq!(ranks_of(&a, &queries), expected);
+    }
+
+    #[test]
+    fn ranks_of_falls_back_on_mutually_incomparable_queries() {
+        // Ordered component-wise, so `(3, 2)` and `(0, 3)` can't be compared
+        #[derive(Debug, PartialEq)]
+        struct Pair(i32, i32);
+        impl PartialOrd for Pair {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                match (self.0.cmp(&other.0), self.1.cmp(&other.1)) {
+                    (x, y) if x == y => Some(x),
+                    (x, Ordering::Equal) | (Ordering::Equal, x) => Some(x),
+                    _ => None,
+                }
+            }
+        }
+
+        let a = [Pair(0, 0), Pair(1, 1), Pair(2, 2), Pair(3, 3)];
+        let queries = [Pair(3, 2), Pair(0, 3)];
+        assert_eq!(ranks_of(&a, &queries), vec![3, 1]);
+    }
+
+    #[test]
+    fn equal_range_then_sort_by_reorders_group() {
+        let people = [
+            (25, "zoe"),
+            (30, "mia"),
+            (30, "bob"),
+            (30, "ann"),
+            (41, "cal"),
+        ];
+        let by_name = |x: &(i32, &str), y: &(i32, &str)| x.1.cmp(y.1);
+        assert_eq!(
+            equal_range_then_sort_by(&people, |p| p.0.cmp(&30), by_name),
+            vec![(30, "ann"), (30, "bob"), (30, "mia")]
+        );
+        assert_eq!(
+            equal_range_then_sort_by(&people, |p| p.0.cmp(&25), by_name),
+            vec![(25, "zoe")]
+        );
+        assert!(equal_range_then_sort_by(&people, |p| p.0.cmp(&35), by_name).is_empty());
+    }
+
+    #[test]
+    fn versioned_bisect_tags_result() {
+        let mut data = vec![1, 3, 5];
+        let mut generation = 7;
+        let (i, seen) = Versioned::new(&data, generation).bisect_left(&4);
+        assert_eq!((i, seen), (bisect_left(&data, &4), 7));
+
+        insort_left(&mut data, 2);
+        generation += 1;
+        let current = Versioned::new(&data, generation);
+        // The earlier result is detectably stale
+        assert_ne!(seen, current.version());
+        assert_eq!(current.bisect_left(&4), (3, 8));
+        assert_eq!(current.as_slice(), &[1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn nth_occurrence_indexes_into_run() {
+        let a = [1, 2, 4, 4, 4, 4, 7];
+        assert_eq!(nth_occurrence(&a, &4, 0), Some(2));
+        assert_eq!(nth_occurrence(&a, &4, 2), Some(4));
+        assert_eq!(nth_occurrence(&a, &4, 3), Some(5));
+        assert_eq!(nth_occurrence(&a, &4, 4), None);
+        assert_eq!(nth_occurrence(&a, &4, usize::MAX), None);
+        assert_eq!(nth_occurrence(&a, &7, 0), Some(6));
+        assert_eq!(nth_occurrence(&a, &3, 0), None);
+        assert_eq!(nth_occurrence(&[] as &[i32], &3, 0), None);
+    }
+
+    #[test]
+    fn closed_range_i64_is_inclusive() {
+        let a = [-5, -1, 0, 0, 3, 8, 8, 12];
+        let cases: [(i64, i64, &[i64]); 7] = [
+            (i64::MIN, i64::MAX, &a),
+            (0, 8, &[0, 0, 3, 8, 8]),
+            (-10, -1, &[-5, -1]),
+            (9, 100, &[12]),
+            (1, 2, &[]),
+            (20, 30, &[]),
+            (8, 0, &[]),
+        ];
+        for &(low, high, expected) in cases.iter() {
+            let range = closed_range_i64(&a, low, high);
+            assert_eq!(range, expected, "[{}, {}]", low, high);
+            assert!(range.iter().all(|&e| low <= e && e <= high));
+        }
+        assert!(closed_range_i64(&[], 0, 1).is_empty());
+    }
+
+    #[test]
+    fn bisect_left_abortable_stops_after_budget() {
+        let a: Vec<_> = (0..100).collect();
+        for x in -1..101 {
+            let never = bisect_left_abortable(&a, |e| Some(e.cmp(&x)));
+            assert_eq!(never, Ok(bisect_left(&a, &x)));
+        }
+
+        // Allow two probes, at 50 and then 75, before aborting
+        let mut budget = 2;
+        let mut cmp = |e: &i32| {
+            if budget == 0 {
+                return None;
+            }
+            budget -= 1;
+            Some(e.cmp(&90))
+        };
+        assert_eq!(bisect_left_abortable(&a, &mut cmp), Err(76));
+
+        assert_eq!(bisect_left_abortable(&a, |_| None), Err(0));
+        assert_eq!(bisect_left_abortable(&[] as &[i32], |_| None), Ok(0));
+    }
+
+    #[test]
+    fn bisect_left_target_map_converts_query() {
+        let meters = [0.5, 1.0, 3.048, 10.0];
+        let feet_to_meters = |ft: &f64| ft * 0.3048;
+        assert_eq!(bisect_left_target_map(&meters, &1.0, feet_to_meters), 0);
+        assert_eq!(bisect_left_target_map(&meters, &2.0, feet_to_meters), 1);
+        assert_eq!(bisect_left_target_map(&meters, &10.0, feet_to_meters), 2);
+        assert_eq!(bisect_left_target_map(&meters, &20.0, feet_to_meters), 3);
+        assert_eq!(bisect_left_target_map(&meters, &40.0, feet_to_meters), 4);
+        // Agrees with converting every probed element to feet instead
+        for &ft in [0.0, 2.0, 5.0, 15.0, 35.0].iter() {
+            assert_eq!(
+                bisect_left_target_map(&meters, &ft, feet_to_meters),
+                bisect_left_mapped(&meters, &ft, |m| m / 0.3048)
+            );
+        }
+    }
+
+    #[test]
+    fn single_element_searches() {
+        let a = [1, 5, 9];
+        for (i, e) in a.iter().enumerate() {
+            for x in e - 1..e + 2 {
+                let within = i..i + 1;
+                let (lt, le) = ((*e < x) as usize, (*e <= x) as usize);
+                assert_eq!(bisect_left_slice(&a, &x, within.clone()), i + lt);
+                assert_eq!(bisect_right_slice(&a, &x, within.clone()), i + le);
+                assert_eq!(
+                    bisect_left_slice_by(&a, within.clone(), |p| p.cmp(&x)),
+                    i + lt
+                );
+                assert_eq!(bisect_right_slice_by(&a, within, |p| x.cmp(p)), i + le);
+            }
+        }
+        assert_eq!(bisect_left(&[5], &5), 0);
+        assert_eq!(bisect_right(&[5], &5), 1);
+    }
+
+    #[test]
+    fn diff_sorted_reports_changes() {
+        let old = [1, 2, 2, 4, 6, 6, 6];
+        let new = [0, 2, 4, 4, 6, 9];
+        assert_eq!(diff_sorted(&old, &new), (vec![0, 4, 9], vec![1, 2, 6, 6]));
+        assert_eq!(diff_sorted(&old, &old), (vec![], vec![]));
+        assert_eq!(diff_sorted(&[], &new), (new.to_vec(), vec![]));
+        assert_eq!(diff_sorted(&old, &[]), (vec![], old.to_vec()));
+    }
+
+    proptest! {
+        #[test]
+        fn test_diff_sorted_matches_counts(
+            mut old in prop::collection::vec(0..20_u32, 0..60),
+            mut new in prop::collection::vec(0..20_u32, 0..60)
+        ) {
+            old.sort();
+            new.sort();
+            let mut counts = std::collections::BTreeMap::new();
+            for x in &new {
+                *counts.entry(*x).or_insert(0_i32) += 1;
+            }
+            for x in &old {
+                *counts.entry(*x).or_insert(0_i32) -= 1;
+            }
+            let (mut added, mut removed) = (Vec::new(), Vec::new());
+            for (&x, &n) in &counts {
+                let side = if n > 0 { &mut added } else { &mut removed };
+                side.extend(vec![x; n.unsigned_abs() as usize]);
+            }
+            assert_eq!(diff_sorted(&old, &new), (added, removed));
+        }
+    }
 
     #[test]
-    fn bisect_right_precomputed() {
-        run_bisect_tests(TestDirection::Right, RIGHT_INT_CASES);
-        run_bisect_tests(TestDirection::Right, RIGHT_FLOAT_CASES);
+    fn bisect_left_rounded_finds_bucket_start() {
+        let a = [1.0, 1.231, 1.234, 1.2349, 1.236, 1.24, 2.0];
+        // 1.231 up to 1.2349 round to 1.23
+        assert_eq!(bisect_left_rounded(&a, 1.2301, 2), 1);
+        assert_eq!(bisect_left_rounded(&a, 1.234, 2), 1);
+        assert_eq!(bisect_left(&a, &1.234), 2);
+        // 1.236 and 1.24 both round to 1.24
+        assert_eq!(bisect_left_rounded(&a, 1.24, 2), 4);
+        assert_eq!(bisect_left_rounded(&a, 1.5, 0), 6);
+        assert_eq!(bisect_left_rounded(&a, 0.0, 2), 0);
+        assert_eq!(bisect_left_rounded(&a, 3.0, 2), 7);
     }
 
     #[test]
-    fn bisect_left_precomputed() {
-        run_bisect_tests(TestDirection::Left, LEFT_INT_CASES);
-        run_bisect_tests(TestDirection::Left, LEFT_FLOAT_CASES);
+    fn weighted_bisect_finds_weighted_median() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let weights = [1.0, 1.0, 5.0, 1.0, 2.0];
+        let half = weights.iter().sum::<f64>() / 2.0;
+        let mut total = 0.0;
+        let manual = weights.iter().position(|w| {
+            total += w;
+            total >= half
+        });
+        assert_eq!(Some(weighted_bisect(&values, &weights, half)), manual);
+        assert_eq!(values[weighted_bisect(&values, &weights, half)], 3.0);
+
+        assert_eq!(weighted_bisect(&values, &weights, 0.0), 0);
+        assert_eq!(weighted_bisect(&values, &weights, 2.0), 1);
+        assert_eq!(weighted_bisect(&values, &weights, 10.0), 4);
+        assert_eq!(weighted_bisect(&values, &weights, 11.0), 5);
     }
 
     #[test]
-    fn bisect_right_slice_precomputed() {
-        run_bisect_slice_tests(TestDirection::Right, RIGHT_INT_CASES);
-        run_bisect_slice_tests(TestDirection::Right, RIGHT_FLOAT_CASES);
+    #[should_panic(expected = "same length")]
+    fn weighted_bisect_checks_lengths() {
+        weighted_bisect(&[1.0, 2.0], &[1.0], 0.5);
     }
 
     #[test]
-    fn bisect_left_slice_precomputed() {
-        run_bisect_slice_tests(TestDirection::Left, LEFT_INT_CASES);
-        run_bisect_slice_tests(TestDirection::Left, LEFT_FLOAT_CASES);
+    fn bisect_right_clamped_index_is_safe() {
+        let a = [1, 3, 3, 5];
+        assert_eq!(bisect_right_clamped_index(&a, &0), Some(0));
+        assert_eq!(bisect_right_clamped_index(&a, &3), Some(3));
+        assert_eq!(a[bisect_right_clamped_index(&a, &4).unwrap()], 5);
+        assert_eq!(bisect_right_clamped_index(&a, &5), None);
+        assert_eq!(bisect_right_clamped_index(&a, &9), None);
+        assert_eq!(bisect_right_clamped_index(&[] as &[i32], &1), None);
     }
 
     #[test]
-    #[should_panic]
-    fn right_slice_index_out_of_bounds() {
-        let a: Vec<u32> = (0..10).collect();
-        // 10 does not fit within 5..10 so the search goes into the out of bounds range
-        bisect_right_slice(&a, &10, 5..15);
+    fn bisect_first_last_by_use_index_positions() {
+        let ascending = [1, 3, 3, 3, 7];
+        assert_eq!(bisect_first_by(&ascending, |e| e.cmp(&3)), 1);
+        assert_eq!(bisect_last_by(&ascending, |e| e.cmp(&3)), 4);
+        assert_eq!(
+            bisect_first_by(&ascending, |e| e.cmp(&3)),
+            bisect_left(&ascending, &3)
+        );
+        assert_eq!(
+            bisect_last_by(&ascending, |e| e.cmp(&3)),
+            bisect_right(&ascending, &3)
+        );
+
+        // Descending, so a comparator in that order has the target's arguments swapped
+        let descending = [9, 5, 5, 5, 2, 0];
+        assert_eq!(bisect_first_by(&descending, |e| 5.cmp(e)), 1);
+        assert_eq!(bisect_last_by(&descending, |e| 5.cmp(e)), 4);
+        assert_eq!(bisect_first_by(&descending, |e| 4.cmp(e)), 4);
+        assert_eq!(bisect_last_by(&descending, |e| 4.cmp(e)), 4);
+        assert_eq!(bisect_first_by(&descending, |e| 10.cmp(e)), 0);
+        assert_eq!(bisect_last_by(&descending, |e| (-1).cmp(e)), 6);
     }
 
     #[test]
-    #[should_panic]
-    fn left_slice_index_out_of_bounds() {
-        let a: Vec<u32> = (0..10).collect();
-        // 10 does not fit within 5..10 so the search goes into the out of bounds range
-        bisect_left_slice(&a, &10, 5..15);
+    fn bisect_left_weak_skips_dropped_entries() {
+        let entries = [
+            (1, 'a'),
+            (3, 'b'),
+            (5, 'c'),
+            (7, 'd'),
+            (9, 'e'),
+            (11, 'f'),
+            (13, 'g'),
+        ];
+        let mut strong: Vec<_> = entries.iter().map(|&e| Some(Arc::new(e))).collect();
+        let weak: Vec<_> = strong
+            .iter()
+            .map(|s| Arc::downgrade(s.as_ref().unwrap()))
+            .collect();
+        let key_of = |e: &(u32, char)| e.0;
+        for (i, k) in [1, 3, 5, 7, 9, 11, 13].iter().enumerate() {
+            assert_eq!(bisect_left_weak(&weak, k, key_of), Some(i));
+        }
+
+        // Drop the middle entry, which is the first probe, and a neighbour
+        strong[3] = None;
+        strong[4] = None;
+        assert_eq!(bisect_left_weak(&weak, &7, key_of), None);
+        assert_eq!(bisect_left_weak(&weak, &9, key_of), None);
+        for (i, k) in [(0, 1), (1, 3), (2, 5), (5, 11), (6, 13)].iter() {
+            assert_eq!(bisect_left_weak(&weak, k, key_of), Some(*i));
+        }
+        assert_eq!(bisect_left_weak(&weak, &4, key_of), None);
+
+        strong.clear();
+        assert_eq!(bisect_left_weak(&weak, &1, key_of), None);
     }
 
     #[test]
-    #[should_panic]
-    fn right_slice_index_out_of_bounds_when_not_searched() {
-        let a: Vec<u32> = (0..10).collect();
-        // 5 fits within 0..10 bounds so the search *doesnt* actually index into the out of bounds
-        bisect_right_slice(&a, &5, ..15);
+    fn max_probes_is_ceil_log2() {
+        let expected = [
+            (0, 0),
+            (1, 1),
+            (2, 2),
+            (3, 2),
+            (4, 3),
+            (7, 3),
+            (8, 4),
+            (1000, 10),
+        ];
+        for &(len, probes) in expected.iter() {
+            assert_eq!(max_probes(len), probes, "{}", len);
+        }
+        assert_eq!(max_probes(usize::MAX), 8 * std::mem::size_of::<usize>());
+    }
+
+    proptest! {
+        #[test]
+        fn test_counted_probes_within_max(
+            mut nums in prop::collection::vec(0..100_u32, 0..600),
+            num in 0..110_u32
+        ) {
+            nums.sort();
+            let (i, probes) = bisect_left_counted(&nums, &num);
+            assert_eq!(i, bisect_left(&nums, &num));
+            assert!(probes <= max_probes(nums.len()), "{} probes", probes);
+            assert_eq!(bisect_left_budgeted(&nums, &num, max_probes(nums.len())), Ok(i));
+        }
     }
 
     #[test]
-    #[should_panic]
-    fn left_slice_index_out_of_bounds_when_not_searched() {
-        let a: Vec<u32> = (0..10).collect();
-        // 5 fits within 0..10 bounds so the search *doesnt* actually index into the out of bounds
-        bisect_left_slice(&a, &5, ..15);
+    fn insort_min_shift_picks_nearer_run_end() {
+        // The run of 2s spans 1..4, nearer the front
+        let mut a = vec![1, 2, 2, 2, 5, 6, 7, 8, 9];
+        assert_eq!(insort_min_shift(&mut a, 2), 1);
+        // The run of 8s spans 8..9, nearer the back
+        assert_eq!(insort_min_shift(&mut a, 8), 9);
+        assert_eq!(insort_min_shift(&mut a, 4), 5);
+        assert!(a.windows(2).all(|w| w[0] <= w[1]));
+
+        // Whatever the run, no other position in it is nearer an end
+        let mut b = vec![0, 3, 3, 3, 3, 3, 3, 9];
+        for &x in [3, 3, 0, 9, 3, 1].iter() {
+            let run = equal_range(&b, &x);
+            let len = b.len();
+            let i = insort_min_shift(&mut b, x);
+            assert!(run.contains(&i) || i == run.end);
+            assert!((run.start..=run.end).all(|j| i.min(len - i) <= j.min(len - j)));
+            assert!(b.windows(2).all(|w| w[0] <= w[1]));
+        }
     }
 
-    fn run_bisect_tests<T: Clone + PartialOrd>(direction: TestDirection, test_cases: TestCollection<T>) {
-        let bisect_func = match direction {
-            TestDirection::Left => bisect_left,
-            TestDirection::Right => bisect_right,
-        };
+    #[test]
+    fn threshold_split_at_cutoff() {
+        let scores = [40, 55, 60, 60, 72, 90];
+        assert_eq!(
+            threshold_split(&scores, &60),
+            (2, &scores[..2], &scores[2..])
+        );
+        assert_eq!(
+            threshold_split_inclusive(&scores, &60),
+            (4, &scores[..4], &scores[4..])
+        );
+        assert_eq!(threshold_split(&scores, &0), (0, &[][..], &scores[..]));
+        assert_eq!(
+            threshold_split_inclusive(&scores, &0),
+            (0, &[][..], &scores[..])
+        );
+        assert_eq!(threshold_split(&scores, &100), (6, &scores[..], &[][..]));
+        assert_eq!(
+            threshold_split_inclusive(&scores, &90),
+            (6, &scores[..], &[][..])
+        );
+        assert_eq!(threshold_split(&scores, &40), (0, &[][..], &scores[..]));
 
-        for test_case in test_cases {
-            let data = test_case.a.to_vec();
-            assert_eq!(test_case.expected_index, bisect_func(&data, &test_case.x));
+        let (_, fail, pass) = threshold_split(&scores, &60);
+        assert!(fail.iter().all(|&s| s < 60) && pass.iter().all(|&s| s >= 60));
+    }
+
+    #[test]
+    fn bisect_left_interp_matches_bisect_left() {
+        fn check<T: Interpolatable + PartialOrd + std::fmt::Debug>(a: &[T], targets: &[T]) {
+            for x in targets {
+                assert_eq!(bisect_left_interp(a, x), bisect_left(a, x), "{:?}", x);
+            }
         }
+        let ints: Vec<i32> = (0..100).map(|i| i * 3 - 50).collect();
+        check(&ints, &(-60..260).collect::<Vec<_>>());
+        let longs: Vec<i64> = (0..100).map(|i| i * 1_000_000_007).collect();
+        check(
+            &longs,
+            &[-1, 0, 5, 1_000_000_007, 50_000_000_000, 200_000_000_000],
+        );
+        let unsigned: Vec<u32> = (0..100).map(|i| i / 4).collect();
+        check(&unsigned, &(0..30).collect::<Vec<_>>());
+        check(&[0_u64, 1, 1 << 40, u64::MAX], &[0, 2, 1 << 40, u64::MAX]);
+        check(&[0_usize, 4, 4, 9], &[0, 3, 4, 10]);
+        let floats: Vec<f32> = (0..100).map(|i| i as f32 * 0.5).collect();
+        check(&floats, &[-1.0, 0.0, 0.25, 10.5, 49.5, 60.0, f32::NAN]);
+        // Skewed keys, where interpolation alone would probe one element at a time
+        let skewed: Vec<f64> = (0..100)
+            .map(|i| if i < 99 { f64::from(i) } else { 1e12 })
+            .collect();
+        check(&skewed, &[0.0, 50.0, 98.5, 1e11, 1e12, 2e12]);
+        check(&[] as &[f64], &[1.0]);
+        check(&[2.0, 2.0, 2.0], &[1.0, 2.0, 3.0]);
     }
 
-    fn run_bisect_slice_tests<T: Clone + PartialOrd>(
-        direction: TestDirection,
-        test_cases: TestCollection<T>,
-    ) {
-        let bisect_func = match direction {
-            TestDirection::Left => bisect_left_slice,
-            TestDirection::Right => bisect_right_slice,
-        };
+    #[test]
+    fn bisect_remove_target_removes_nearest() {
+        let dist = |e: &i32, x: &i32| e.abs_diff(*x);
+        let mut a = vec![1, 4, 4, 10, 20];
+        assert_eq!(bisect_remove_target(&mut a, &4, dist), Some(4));
+        assert_eq!(a, vec![1, 4, 10, 20]);
+        // 8 is nearer 10 than 4
+        assert_eq!(bisect_remove_target(&mut a, &8, dist), Some(10));
+        // With 10 gone, 12 is as near 4 as 20, so the smaller goes
+        assert_eq!(bisect_remove_target(&mut a, &12, dist), Some(4));
+        assert_eq!(bisect_remove_target(&mut a, &0, dist), Some(1));
+        assert_eq!(bisect_remove_target(&mut a, &99, dist), Some(20));
+        assert_eq!(bisect_remove_target(&mut a, &1, dist), None);
 
-        for test_case in test_cases {
-            let data = test_case.a.to_vec();
-            for lo in 0..4 {
-                for hi in 3..8 {
-                    let hi = std::cmp::min(data.len(), hi);
-                    let ip = bisect_func(&data, &test_case.x, lo..hi);
+        let mut b = vec![1.0, 3.0];
+        assert_eq!(
+            bisect_remove_target(&mut b, &2.0, |e: &f64, x: &f64| (e - x).abs()),
+            Some(1.0)
+        );
+        assert_eq!(b, vec![3.0]);
+    }
 
-                    match direction {
-                        TestDirection::Left => {
-                            if ip < hi {
-                                assert!(test_case.x <= data[ip]);
-                            }
+    #[test]
+    fn bisect_remove_target_at_integer_extremes() {
+        let mut a = vec![-100i8, 100];
+        assert_eq!(
+            bisect_remove_target(&mut a, &90, |e, x| e.abs_diff(*x)),
+            Some(100)
+        );
 
-                            if ip > lo {
-                                assert!(data[ip - 1] < test_case.x)
-                            }
-                        }
-                        TestDirection::Right => {
-                            if ip < hi {
-                                assert!(test_case.x < data[ip]);
-                            }
+        let mut a = vec![i32::MIN, 10];
+        assert_eq!(
+            bisect_remove_target(&mut a, &5, |e, x| e.abs_diff(*x)),
+            Some(10)
+        );
 
-                            if ip > lo {
-                                assert!(data[ip - 1] <= test_case.x)
-                            }
-                        }
-                    }
+        let mut a = vec![i64::MIN, i64::MAX];
+        assert_eq!(
+            bisect_remove_target(&mut a, &-1, |e, x| e.abs_diff(*x)),
+            Some(i64::MIN)
+        );
+        assert_eq!(a, vec![i64::MAX]);
 
-                    assert_eq!(
-                        ip,
-                        std::cmp::max(lo, std::cmp::min(hi, test_case.expected_index))
-                    );
-                }
-            }
-        }
+        let mut a = vec![0u8, u8::MAX];
+        assert_eq!(
+            bisect_remove_target(&mut a, &200, |e, x| e.abs_diff(*x)),
+            Some(u8::MAX)
+        );
     }
 
-    #[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
-    struct Person {
-        name: String,
-        age: u32,
+    #[test]
+    fn bisect_left_banded_filters_within_run() {
+        // Colors sorted by brightness, matched within a tolerance of a target hue
+        let colors = [
+            (10, 200),
+            (50, 30),
+            (50, 120),
+            (50, 125),
+            (50, 250),
+            (90, 120),
+        ];
+        let near_hue = |hue: i32| move |c: &(i32, i32)| (c.1 - hue).abs() <= 10;
+        assert_eq!(
+            bisect_left_banded(&colors, |c| c.0.cmp(&50), near_hue(118)),
+            Some(2)
+        );
+        assert_eq!(
+            bisect_left_banded(&colors, |c| c.0.cmp(&50), near_hue(245)),
+            Some(4)
+        );
+        assert_eq!(
+            bisect_left_banded(&colors, |c| c.0.cmp(&50), near_hue(200)),
+            None
+        );
+        assert_eq!(
+            bisect_left_banded(&colors, |c| c.0.cmp(&90), near_hue(125)),
+            Some(5)
+        );
+        assert_eq!(
+            bisect_left_banded(&colors, |c| c.0.cmp(&70), |_| true),
+            None
+        );
     }
 
-    fn arb_person() -> impl Strategy<Value = Person> {
-        ("[a-z]*", 1..100_u32).prop_map(|(name, age)| Person { name, age })
+    #[test]
+    fn bracket_left_collapses_to_bisect_left() {
+        let a = [1, 2, 2, 4, 4, 4, 8];
+        for x in 0..10 {
+            let i = bisect_left(&a, &x);
+            assert_eq!(bracket_left(&a, &x), (i, i));
+        }
+        assert_eq!(bracket_left(&[] as &[i32], &1), (0, 0));
     }
 
-    fn check_index_right_invariant<T, F>(a: &[T], target: &T, index: usize, mut f: F)
-    where
-        F: FnMut(&T, &T) -> Ordering,
-    {
-        // See `bisect_right_by` docs
-        assert!(a[..index].iter().all(|x| match f(x, &target) {
-            Ordering::Less | Ordering::Equal => true,
-            _ => false,
-        }));
-        assert!(a[index..]
-            .iter()
-            .all(|x| f(x, &target) == Ordering::Greater));
+    #[test]
+    fn streaming_insort_bounds_reallocations() {
+        let n = 10_000;
+        let stream = (0..n as u64).map(|i| i.wrapping_mul(2_654_435_761) % 1000);
+        let mut chunked = StreamingInsort::new(GrowthPolicy::Chunked(256));
+        let mut geometric = StreamingInsort::new(GrowthPolicy::Geometric);
+        let mut shifted = 0;
+        for x in stream {
+            chunked.insert(x);
+            let i = geometric.insert(x);
+            shifted += geometric.as_slice().len() - 1 - i;
+        }
+        // At most ⌈10_000 / 256⌉ chunks
+        assert!(chunked.stats().reallocations <= 40);
+        assert!(geometric.stats().reallocations <= 15);
+        assert_eq!(chunked.stats().shifted, shifted);
+        assert_eq!(geometric.stats().shifted, shifted);
+
+        let a = chunked.into_vec();
+        assert_eq!(a.len(), n);
+        assert!(a.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(a, geometric.into_vec());
     }
 
-    fn check_index_left_invariant<T, F>(a: &[T], target: &T, index: usize, mut f: F)
-    where
-        F: FnMut(&T, &T) -> Ordering,
-    {
-        // See `bisect_left_by` docs
-        assert!(a[..index].iter().all(|x| f(x, &target) == Ordering::Less));
-        assert!(a[index..].iter().all(|x| match f(x, &target) {
-            Ordering::Greater | Ordering::Equal => true,
-            _ => false,
-        }));
+    #[test]
+    #[should_panic(expected = "chunk size must be nonzero")]
+    fn streaming_insort_rejects_empty_chunks() {
+        StreamingInsort::<i32>::new(GrowthPolicy::Chunked(0));
     }
 
     proptest! {
-
         #[test]
-        fn test_bisect_left_index_invariant(
-            mut nums in prop::collection::vec(any::<u32>(), 0..500),
-            num in any::<u32>()
-        ) {
-            nums.sort();
+        fn test_argsort_insort_matches_stable_sort(a in prop::collection::vec(0..20_u32, 0..100)) {
+            let mut expected: Vec<usize> = (0..a.len()).collect();
+            expected.sort_by_key(|&i| a[i]);
+            assert_eq!(argsort_insort(&a), expected);
+        }
+    }
 
-            let i = bisect_left(&nums, &num);
+    #[test]
+    fn argsort_insort_is_stable() {
+        assert_eq!(argsort_insort(&["b", "a", "c", "a"]), vec![1, 3, 0, 2]);
+        assert_eq!(argsort_insort::<i32>(&[]), Vec::<usize>::new());
+    }
 
-            check_index_left_invariant(&nums, &num, i, u32::cmp);
-        }
+    #[test]
+    fn bisect_fingerprint_resolves_collisions() {
+        let fingerprints = [3, 17, 17, 17, 42];
+        let keys = ["ant", "bee", "cat", "dog", "eel"];
+        let lookup = |key: &str, fp| bisect_fingerprint(&fingerprints, fp, |i| keys[i] == key);
+        assert_eq!(lookup("cat", 17), Some(2));
+        assert_eq!(lookup("dog", 17), Some(3));
+        assert_eq!(lookup("eel", 17), None);
+        assert_eq!(lookup("eel", 42), Some(4));
+        assert_eq!(lookup("ant", 5), None);
+    }
 
-        #[test]
-        fn test_bisect_left_by_index_invariant(
-            mut people in prop::collection::vec(arb_person(), 0..500),
-            new_person in arb_person()
-        ) {
-            // Sort by age only
-            let f = |a: &Person, b: &Person| a.age.cmp(&b.age);
+    #[test]
+    fn advance_past_matches_bisect_right() {
+        let long = [1, 2, 2, 3, 5, 8, 8, 13];
+        for pivot in 0..15 {
+            assert_eq!(advance_past(&long, &pivot), bisect_right(&long, &pivot));
+        }
+        assert_eq!(advance_past(&long, &2), 3);
+        assert_eq!(advance_past(&long[3..], &8), 4);
+        assert_eq!(advance_past(&[] as &[i32], &1), 0);
+    }
 
-            people.sort_by(f);
+    #[test]
+    fn fixed_point_bisect_is_exact() {
+        // Prices in cents
+        let mut prices = vec![99, 1_000, 1_250, 1_250, 4_999];
+        assert_eq!(bisect_left_fixed_point(&prices, 1_250), 2);
+        assert_eq!(bisect_left_fixed_point(&prices, 1_251), 4);
+        assert_eq!(insort_right_fixed_point(&mut prices, 1_250), 4);
+        assert_eq!(insort_right_fixed_point(&mut prices, 0), 0);
+        assert_eq!(prices, vec![0, 99, 1_000, 1_250, 1_250, 1_250, 4_999]);
+    }
 
-            let i = bisect_left_by(&people, |p| f(p, &new_person));
+    #[test]
+    fn bisect_left_decimal_rounds_half_to_even() {
+        let cents = [12, 13, 14, 100, 101, 250];
+        // Exactly on the scale
+        assert_eq!(bisect_left_decimal(&cents, 0.13, 2), 1);
+        assert_eq!(bisect_left_decimal(&cents, 2.5, 2), 5);
+        assert_eq!(bisect_left_decimal(&cents, 1.0, 2), 3);
+        // Ties go to the even neighbour: 12.5 to 12, 13.5 to 14
+        assert_eq!(bisect_left_decimal(&cents, 0.125, 2), 0);
+        assert_eq!(bisect_left_decimal(&[12, 13, 14], 0.135, 2), 2);
+        assert_eq!(bisect_left_decimal(&cents, -0.005, 2), 0);
+        // Not ties: ordinary rounding to nearest
+        assert_eq!(bisect_left_decimal(&cents, 0.1349, 2), 1);
+        assert_eq!(bisect_left_decimal(&cents, 0.1351, 2), 2);
+        assert_eq!(bisect_left_decimal(&cents, 1.006, 2), 4);
+        assert_eq!(bisect_left_decimal(&cents, 1.005, 2), 3);
+        // Scale 0 and saturation
+        assert_eq!(bisect_left_decimal(&cents, 12.5, 0), 0);
+        assert_eq!(bisect_left_decimal(&cents, 13.5, 0), 2);
+        assert_eq!(bisect_left_decimal(&cents, 1e300, 2), 6);
+        assert_eq!(bisect_left_decimal(&cents, -1e300, 2), 0);
+    }
 
-            check_index_left_invariant(&people, &new_person, i, f);
+    #[test]
+    fn insort_strict_rejects_duplicates() {
+        let mut a = Vec::new();
+        assert_eq!(insort_strict(&mut a, 5), Ok(0));
+        assert_eq!(insort_strict(&mut a, 1), Ok(0));
+        assert_eq!(insort_strict(&mut a, 9), Ok(2));
+        assert_eq!(insort_strict(&mut a, 3), Ok(1));
+        assert_eq!(a, vec![1, 3, 5, 9]);
 
-            // Sort by name only
-            let f = |a: &Person, b: &Person| a.name.cmp(&b.name);
+        assert_eq!(insort_strict(&mut a, 5), Err((2, 5)));
+        assert_eq!(a, vec![1, 3, 5, 9]);
+        assert_eq!(assert_unique_sorted(&a), Ok(()));
 
-            people.sort_by(f);
+        // The rejected value is handed back, not dropped
+        let mut names = vec![String::from("ann")];
+        let rejected = insort_strict(&mut names, String::from("ann")).unwrap_err();
+        assert_eq!(rejected, (0, String::from("ann")));
+    }
 
-            let i = bisect_left_by(&people, |p| f(p, &new_person));
+    #[test]
+    fn soa_table_queries_index_payloads() {
+        let (mut keys, mut names) = (Vec::new(), Vec::new());
+        for &(k, name) in [(20, "b"), (10, "a"), (20, "c"), (30, "d"), (10, "e")].iter() {
+            insort_right_soa(&mut keys, &mut names, k, name);
+        }
+        assert_eq!(keys, vec![10, 10, 20, 20, 30]);
+        assert_eq!(names, vec!["a", "e", "b", "c", "d"]);
 
-            check_index_left_invariant(&people, &new_person, i, f);
+        for &(k, expected) in [(10, &["a", "e"][..]), (20, &["b", "c"]), (30, &["d"])].iter() {
+            let rows = soa_equal_range(&keys, &k);
+            assert!(keys[rows.clone()].iter().all(|&e| e == k));
+            assert_eq!(&names[rows], expected);
         }
+        assert_eq!(soa_equal_range(&keys, &15), 2..2);
+        assert_eq!(bisect_left_soa(&keys, &20), 2);
+        assert_eq!(names[bisect_left_soa(&keys, &30)], "d");
+    }
 
-        #[test]
-        fn test_bisect_right_index_invariant(
-            mut nums in prop::collection::vec(any::<u32>(), 0..500),
-            num in any::<u32>()
-        ) {
-            nums.sort();
+    #[test]
+    #[should_panic(expected = "same length")]
+    fn insort_right_soa_checks_alignment() {
+        insort_right_soa(&mut vec![1, 2], &mut vec!["a"], 3, "b");
+    }
 
-            let i = bisect_right(&nums, &num);
+    #[test]
+    fn bisect_slice_empty_range() {
+        let a = [1, 2, 3, 4, 5, 6];
+        assert_eq!(bisect_left_slice(&a, &4, 3..3), 3);
+        assert_eq!(bisect_right_slice(&a, &4, 3..3), 3);
+        assert_eq!(bisect_left_slice_by(&a, 3..3, |e| e.cmp(&4)), 3);
+        assert_eq!(bisect_right_slice_by(&a, 3..3, |e| 4.cmp(e)), 3);
+        assert_eq!(bisect_left(&[] as &[i32], &4), 0);
+        assert_eq!(bisect_right(&[] as &[i32], &4), 0);
+        assert_eq!(bisect_left_by(&[] as &[i32], |e| e.cmp(&4)), 0);
+        assert_eq!(bisect_right_by(&[] as &[i32], |e| 4.cmp(e)), 0);
+    }
 
-            check_index_right_invariant(&nums, &num, i, u32::cmp)
+    #[test]
+    fn bisect_left_seek_huge_count_does_not_overflow() {
+        // `size`-byte records reading all `0` before record `edge` and all `1` from it on,
+        // without backing storage
+        struct Steps {
+            pos: u64,
+            size: u64,
+            edge: u64,
+        }
+        impl Read for Steps {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                buf.fill((self.pos / self.size >= self.edge) as u8);
+                self.pos = self.pos.saturating_add(buf.len() as u64);
+                Ok(buf.len())
+            }
+        }
+        impl Seek for Steps {
+            fn seek(&mut self, from: SeekFrom) -> io::Result<u64> {
+                if let SeekFrom::Start(pos) = from {
+                    self.pos = pos;
+                }
+                Ok(self.pos)
+            }
         }
 
-        #[test]
-        fn test_bisect_right_by_index_invariant(
-            mut people in prop::collection::vec(arb_person(), 0..500),
-            new_person in arb_person()
-        ) {
-            // Sort by age only
-            let f = |a: &Person, b: &Person| a.age.cmp(&b.age);
+        let count = usize::MAX / 2 + 10;
+        let edge = count as u64 - 3;
+        let mut reader = Steps {
+            pos: 0,
+            size: 1,
+            edge,
+        };
+        let i = bisect_left_seek(&mut reader, count, 1, &[1], |r, t| r.cmp(t)).unwrap();
+        assert_eq!(i as u64, edge);
 
-            people.sort_by(f);
+        // Wider records whose offsets all fit in a `u64`
+        let count = usize::MAX / 16;
+        let edge = count as u64 - 3;
+        let mut reader = Steps {
+            pos: 0,
+            size: 8,
+            edge,
+        };
+        let i = bisect_left_seek(&mut reader, count, 8, &[1; 8], |r, t| r.cmp(t)).unwrap();
+        assert_eq!(i as u64, edge);
 
-            let i = bisect_right_by(&people, |p| f(&new_person, p));
+        // Probing near the end of this many records would seek past `u64::MAX`
+        let count = usize::MAX / 4;
+        let mut reader = Steps {
+            pos: 0,
+            size: 8,
+            edge: u64::MAX,
+        };
+        let err = bisect_left_seek(&mut reader, count, 8, &[1; 8], |r, t| r.cmp(t)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
 
-            check_index_right_invariant(&people, &new_person, i, f);
+    #[test]
+    fn insort_all_right_with_capacity_does_not_reallocate_reserved() {
+        let mut a: Vec<u32> = (0..10_000).map(|i| i * 2).collect();
+        a.reserve(16);
+        let xs: Vec<u32> = (0..16).map(|i| (i * 7_919) % 20_000).collect();
+        let mut expected = a.clone();
+        for &x in &xs {
+            insort_right(&mut expected, x);
+        }
 
+        let (capacity, ptr) = (a.capacity(), a.as_ptr());
+        insort_all_right_with_capacity(&mut a, xs, 0);
+        assert_eq!(a.capacity(), capacity);
+        assert_eq!(a.as_ptr(), ptr);
+        assert_eq!(a, expected);
+    }
 
-            // Sort by name only
-            let f = |a: &Person, b: &Person| a.name.cmp(&b.name);
+    #[test]
+    fn insort_all_right_keeps_existing_before_equal_new() {
+        // Ordered by the key alone, so equal keys are told apart by their tag
+        #[derive(Debug, PartialEq, Eq)]
+        struct Tagged(u32, char);
+        impl PartialOrd for Tagged {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Tagged {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.cmp(&other.0)
+            }
+        }
 
-            people.sort_by(f);
+        let mut a = vec![Tagged(1, 'a'), Tagged(3, 'a'), Tagged(3, 'b')];
+        let xs = vec![
+            Tagged(3, 'x'),
+            Tagged(0, 'y'),
+            Tagged(3, 'z'),
+            Tagged(1, 'w'),
+        ];
+        insort_all_right(&mut a, xs);
+        let tags: String = a.iter().map(|t| t.1).collect();
+        assert_eq!(tags, "yawabxz");
 
-            let i = bisect_right_by(&people, |p| f(&new_person, p));
+        // Either side empty
+        let mut a = vec![Tagged(2, 'a')];
+        insort_all_right(&mut a, vec![]);
+        assert_eq!(a, vec![Tagged(2, 'a')]);
+        let mut a = vec![];
+        insort_all_right(&mut a, vec![Tagged(2, 'b'), Tagged(1, 'c')]);
+        assert_eq!(a, vec![Tagged(1, 'c'), Tagged(2, 'b')]);
+    }
 
-            check_index_right_invariant(&people, &new_person, i, f);
+    #[test]
+    fn insort_all_right_keeps_every_element_when_a_comparison_panics() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        // Panics on the first comparison between an existing and a new element
+        #[derive(Debug)]
+        struct Fragile(u32, bool, Rc<Cell<usize>>);
+        impl Drop for Fragile {
+            fn drop(&mut self) {
+                self.2.set(self.2.get() + 1);
+            }
+        }
+        impl PartialEq for Fragile {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+        impl Eq for Fragile {}
+        impl PartialOrd for Fragile {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Fragile {
+            fn cmp(&self, other: &Self) -> Ordering {
+                if self.1 != other.1 {
+                    panic!("mixed comparison");
+                }
+                self.0.cmp(&other.0)
+            }
         }
 
-        #[test]
-        fn test_insort_vs_vec_sort(
-            digits in prop::collection::vec(0..10, 0..500)
-        ) {
-            let left_digits = HashSet::<i32>::from_iter(vec![0, 2, 4, 6, 8]);
-            let mut insorted = vec![];
+        let drops = Rc::new(Cell::new(0));
+        let mut a: Vec<Fragile> = (0..5).map(|i| Fragile(i, false, drops.clone())).collect();
+        let xs: Vec<Fragile> = (0..3).map(|i| Fragile(i, true, drops.clone())).collect();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            insort_all_right(&mut a, xs)
+        }));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 0);
 
-            for digit in digits {
-                let f = if  left_digits.contains(&digit) {
-                    insort_left
-                } else {
-                    insort_right
-                };
+        let mut keys: Vec<(bool, u32)> = a.iter().map(|f| (f.1, f.0)).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                (false, 0),
+                (false, 1),
+                (false, 2),
+                (false, 3),
+                (false, 4),
+                (true, 0),
+                (true, 1),
+                (true, 2)
+            ]
+        );
+        drop(a);
+        assert_eq!(drops.get(), 8);
+    }
 
-                f(&mut insorted, digit);
-            }
+    #[test]
+    fn bisect_left_weak_returns_leftmost_live_duplicate() {
+        let keys = [1, 4, 4, 4, 4, 4, 4, 4, 9];
+        let mut strong: Vec<_> = keys.iter().map(|&k| Some(Arc::new(k))).collect();
+        let weak: Vec<_> = strong
+            .iter()
+            .map(|s| Arc::downgrade(s.as_ref().unwrap()))
+            .collect();
+        let key_of = |k: &u32| *k;
+        assert_eq!(bisect_left_weak(&weak, &4, key_of), Some(1));
 
-            let vec_sorted = {
-                let mut v = insorted.clone();
-                v.sort();
-                v
-            };
+        // The leftmost duplicates die, so the first live one is further right
+        strong[1] = None;
+        strong[2] = None;
+        assert_eq!(bisect_left_weak(&weak, &4, key_of), Some(3));
 
-            assert_eq!(vec_sorted, insorted);
+        // Only a stray live duplicate past dead ones remains, right of the first probe
+        for s in &mut strong[3..7] {
+            *s = None;
         }
+        assert_eq!(bisect_left_weak(&weak, &4, key_of), Some(7));
+        assert_eq!(bisect_left_weak(&weak, &9, key_of), Some(8));
+        assert_eq!(bisect_left_weak(&weak, &1, key_of), Some(0));
     }
 }